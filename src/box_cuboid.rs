@@ -1,64 +1,313 @@
-use anyhow::Result;
-use clap::{ArgMatches, Command};
-use rust_decimal::prelude::ToPrimitive;
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rust_decimal::Decimal;
+
+use crate::common::args::{
+    cli_help_arg, Clearance, GlueFlap, GlueFlapAngle, GlueFlapStyle, HandleHeight, HandleOffset,
+    HandleShape, HandleWidth, Height, Length, NotchOn, Thickness, ThumbNotch, Width,
+};
+use crate::common::{
+    file_name, Borders, CutType, DrawResult, FlapStyle, Handle, Net, Notch, NotchPlace, Origin,
+    Point, Role, Side, Square, SquareElement, HANDLE_MIN_WIDTH, VIEWPORT_OFFSET,
+};
+use crate::lid::{InnerWalls, LidBorders, LidHeight};
 
-use crate::common::args::{cli_help_arg, GlueFlap, Height, Length, Thickness, Width};
-use crate::common::{Borders, CutType, DrawResult, Origin, Point, SquareElement, VIEWPORT_OFFSET};
-use crate::lid::LidHeight;
+pub const CLI_SUBCOMMAND: &str = "box-cuboid";
 
-const BOX_CUBE_FIE_NAME: &str = "LaserCutBoxCube.svg";
+pub struct Handles;
+impl Handles {
+    const NAME: &'static str = "handles";
 
-pub const CLI_SUBCOMMAND: &str = "box-cuboid";
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handles")
+            .action(ArgAction::SetTrue)
+            .help("Ручки в боковых стенках.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> bool {
+        m.get_flag(Self::NAME)
+    }
+}
+
+pub struct HandleTop;
+impl HandleTop {
+    const NAME: &'static str = "handletop";
+
+    const DEFAULT: &'static str = "20";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handle-top")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Отступ ручки от верха стенки (мм), ручка не заходит под крышку.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct Nest;
+impl Nest {
+    const NAME: &'static str = "nest";
+
+    const DEFAULT: &'static str = "1";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("nest")
+            .value_parser(value_parser!(u8).range(1..))
+            .default_value(Self::DEFAULT)
+            .help("Количество коробок, вложенных одна в другую. Размеры задают самую большую коробку.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> u8 {
+        m.get_one(Self::NAME).copied().unwrap_or(1)
+    }
+}
+
+pub struct NestContents;
+impl NestContents {
+    const NAME: &'static str = "nestcontents";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("nest-contents")
+            .action(ArgAction::SetTrue)
+            .help("Размеры задают содержимое самой маленькой из вложенных коробок.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> bool {
+        m.get_flag(Self::NAME)
+    }
+}
 
 pub fn cli_build(root: Command) -> Command {
     let c = Command::new(CLI_SUBCOMMAND)
         .about("Коробка-параллелипипед с крышкой.")
         .arg(cli_help_arg())
-        .arg_required_else_help(true)
-        .arg(Length::arg())
+        .arg_required_else_help(true);
+
+    let c = cli_args(c)
+        .arg(Nest::arg())
+        .arg(NestContents::arg())
+        .arg(Clearance::arg());
+
+    root.subcommand(c)
+}
+
+/// Arguments of the box, shared with the models built on it
+pub fn cli_args(c: Command) -> Command {
+    c.arg(Length::arg())
         .arg(Width::arg())
         .arg(Height::arg())
         .arg(LidHeight::arg())
+        .arg(LidBorders::arg())
         .arg(GlueFlap::arg())
+        .arg(GlueFlapStyle::arg())
+        .arg(GlueFlapAngle::arg())
+        .arg(Handles::arg())
+        .arg(HandleShape::arg())
+        .arg(HandleHeight::arg())
+        .arg(HandleWidth::arg())
+        .arg(HandleOffset::arg())
+        .arg(HandleTop::arg())
+        .arg(ThumbNotch::arg())
+        .arg(NotchOn::arg())
         .arg(Thickness::arg())
-        ;
-
-    root.subcommand(c)
 }
 
 pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
     let cfg = BoxCubeCfg::new(m)?;
+    let count = Nest::extract(m);
+    if count > 1 {
+        return draw_nest(
+            cfg,
+            count,
+            NestContents::extract(m),
+            Clearance::extract(m).unwrap(),
+        );
+    }
     log::info!("Коробка-параллелипипед в работе.");
 
     let bx = BoxCube::new(cfg);
     Ok(bx.draw())
 }
 
-#[derive(Debug)]
+/// Set of boxes which go one into another, the largest one is drawn first
+fn draw_nest(cfg: BoxCubeCfg, count: u8, contents: bool, clearance: Decimal) -> Result<DrawResult> {
+    if clearance < Decimal::ZERO {
+        bail!("Зазор не может быть отрицательным: {clearance}");
+    }
+    log::info!("Набор из {count} вложенных коробок в работе.");
+
+    let mut boxes = vec![if contents {
+        cfg.holding(cfg.size(), clearance)
+    } else {
+        cfg
+    }];
+    while boxes.len() < usize::from(count) {
+        let last = boxes.last().unwrap();
+        boxes.push(if contents {
+            last.around(clearance)
+        } else {
+            last.inside(clearance)
+        });
+    }
+    if contents {
+        boxes.reverse();
+    }
+
+    let mut result = DrawResult::empty(
+        boxes[0]
+            .file_name()
+            .replace(".svg", &format!("_nest{count}.svg")),
+    );
+    for (i, cfg) in boxes.into_iter().enumerate() {
+        let [l, w, h] = cfg.size();
+        log::info!("Коробка {}: {l}x{w}x{h}мм", i + 1);
+        if w <= cfg.thick_n(4) || l <= cfg.thick_n(4) || h <= cfg.lid_height {
+            bail!(
+                "Коробка {} получилась слишком маленькой для крышки высотой {}мм",
+                i + 1,
+                cfg.lid_height
+            );
+        }
+
+        let mut drawing = BoxCube::new(cfg).below(&result).draw();
+        drawing.net.prefix(&format!("nest{}-", i + 1));
+        result.append(drawing);
+    }
+
+    Ok(result)
+}
+
+/// Box without the attached lid
+pub fn draw_base(m: &ArgMatches) -> Result<DrawResult> {
+    let cfg = BoxCubeCfg {
+        attached_lid: false,
+        ..BoxCubeCfg::new(m)?
+    };
+
+    let bx = BoxCube::new(cfg);
+    Ok(bx.draw())
+}
+
+#[derive(Debug, Clone)]
 pub struct BoxCubeCfg {
-    thickness: f64,
-    glue_flap: f64,
-    lid_height: f64,
-    height: f64,
-    length: f64,
-    width: f64,
+    thickness: Decimal,
+    glue_flap: Decimal,
+    flap_style: FlapStyle,
+    /// Handles in the side walls
+    handle: Option<Handle>,
+    handle_top: Decimal,
+    /// Thumb notch to open the lid
+    notch: Option<Notch>,
+    lid_height: Decimal,
+    /// Double side walls of the lid
+    lid_fat: bool,
+    /// Lid is a part of the box net
+    attached_lid: bool,
+    height: Decimal,
+    length: Decimal,
+    width: Decimal,
 }
 
 impl BoxCubeCfg {
-    pub fn thick_n(&self, multiply: usize) -> f64 {
-        self.thickness * multiply as f64
+    pub fn thick_n(&self, multiply: usize) -> Decimal {
+        self.thickness * Decimal::from(multiply)
+    }
+
+    /// Lid side walls stick out of the box by this, inner layers of the fat lid are next to them
+    fn lid_side(&self) -> Decimal {
+        if !self.attached_lid {
+            Decimal::ZERO
+        } else if self.lid_fat {
+            self.lid_height * Decimal::TWO + self.thick_n(2)
+        } else {
+            self.lid_height + self.thickness
+        }
+    }
+
+    fn file_name(&self) -> String {
+        file_name(CLI_SUBCOMMAND, self.size(), self.thickness)
+    }
+
+    fn size(&self) -> [Decimal; 3] {
+        [self.length, self.width, self.height]
+    }
+
+    /// The attached lid goes around the walls and lies on top of them
+    fn lid_extra(&self) -> [Decimal; 3] {
+        if !self.attached_lid {
+            return [Decimal::ZERO; 3];
+        }
+        let fat = if self.lid_fat { 2 } else { 1 };
+        [self.thick_n(2 * fat), self.thick_n(2), self.thickness]
+    }
+
+    /// Same box of the other size
+    fn resized(&self, [length, width, height]: [Decimal; 3]) -> Self {
+        Self {
+            length,
+            width,
+            height,
+            ..self.clone()
+        }
+    }
+
+    /// Box to hold `contents` with `clearance` on each side and above
+    fn holding(&self, contents: [Decimal; 3], clearance: Decimal) -> Self {
+        let [l, w, h] = contents;
+        let gap = clearance * Decimal::TWO;
+        self.resized([
+            l + gap + self.thick_n(2),
+            w + gap + self.thick_n(2),
+            h + clearance + self.thickness,
+        ])
+    }
+
+    /// Next larger box of the nest, this one with its lid fits in
+    fn around(&self, clearance: Decimal) -> Self {
+        let extra = self.lid_extra();
+        let [l, w, h] = self.size();
+        self.holding([l + extra[0], w + extra[1], h + extra[2]], clearance)
+    }
+
+    /// Next smaller box of the nest, it fits in this one together with its lid
+    fn inside(&self, clearance: Decimal) -> Self {
+        let extra = self.lid_extra();
+        let gap = clearance * Decimal::TWO;
+        let [l, w, h] = self.size();
+        self.resized([
+            l - self.thick_n(2) - gap - extra[0],
+            w - self.thick_n(2) - gap - extra[1],
+            h - self.thickness - clearance - extra[2],
+        ])
     }
 }
 
 impl BoxCubeCfg {
     pub fn new(m: &ArgMatches) -> Result<Self> {
         Ok(Self {
-            thickness: Thickness::extract(m).unwrap().to_f64().unwrap(),
-            glue_flap: GlueFlap::extract(m).unwrap().to_f64().unwrap(),
-            lid_height: LidHeight::extract(m).unwrap().to_f64().unwrap(),
-            height: Height::extract(m).unwrap().to_f64().unwrap(),
-            length: Length::extract(m).unwrap().to_f64().unwrap(),
-            width: Width::extract(m).unwrap().to_f64().unwrap(),
+            thickness: Thickness::extract(m).unwrap(),
+            glue_flap: GlueFlap::extract(m)?,
+            flap_style: GlueFlapStyle::extract(m)?,
+            handle: if Handles::extract(m) {
+                Some(Handle::from_matches(m)?)
+            } else {
+                None
+            },
+            handle_top: HandleTop::extract(m).unwrap(),
+            notch: Notch::from_matches(m)?,
+            lid_height: LidHeight::extract(m).unwrap(),
+            lid_fat: LidBorders::extract(m),
+            attached_lid: true,
+            height: Height::extract(m).unwrap(),
+            length: Length::extract(m).unwrap(),
+            width: Width::extract(m).unwrap(),
         })
     }
 }
@@ -72,16 +321,27 @@ struct BoxCube {
 impl BoxCube {
     fn new(cfg: BoxCubeCfg) -> Self {
         // Initial offset
-        let offset = Point::new(cfg.height, 0.0).shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
+        let offset = Point::new(cfg.height.max(cfg.lid_side()), Decimal::ZERO)
+            .shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
+        let mut result = DrawResult::empty(cfg.file_name());
+        result.net = Net::new(cfg.thickness);
         Self {
             cfg,
             offset,
-            result: DrawResult::empty(BOX_CUBE_FIE_NAME.into()),
+            result,
         }
     }
 
+    /// Start the drawing below `other`
+    fn below(mut self, other: &DrawResult) -> Self {
+        self.offset.y = other.max.y + VIEWPORT_OFFSET;
+        self
+    }
+
     fn draw(mut self) -> DrawResult {
-        self.draw_top_lid();
+        if self.cfg.attached_lid {
+            self.draw_top_lid();
+        }
         self.draw_main_walls();
 
         self.result
@@ -96,19 +356,36 @@ impl BoxCube {
     }
 
     fn draw_top_lid(&mut self) {
-        let lid_len = self.cfg.length + self.cfg.thick_n(2);
+        // Fat lid is wider by the inner layers of the side walls
+        let fat = if self.cfg.lid_fat { 1 } else { 0 };
+        let lid_len = self.cfg.length + self.cfg.thick_n(2 + fat * 2);
         let lid_width = self.cfg.width + self.cfg.thick_n(2);
-        let offset = self.offset.shift_nx(self.cfg.thick_n(1));
+        let offset = self.offset.shift_nx(self.cfg.thick_n(1 + fat));
 
         let top_flap = SquareElement::new(
-            lid_len - self.cfg.glue_flap * 2.0,
+            lid_len - self.cfg.glue_flap * Decimal::TWO,
             self.cfg.lid_height - self.cfg.thickness,
         )
         .with_borders(Borders::new_cut())
-        .border_bottom(CutType::Bend);
+        .border_bottom(CutType::Bend)
+        .role(Role::Flap);
 
-        self.result
-            .append(top_flap.draw(offset.shift_x(self.cfg.glue_flap)));
+        self.result.append(
+            top_flap
+                .named("lid-front-flap")
+                .hinged("lid-front", Side::Bottom, 180.0)
+                .draw(offset.shift_x(self.cfg.glue_flap)),
+        );
+
+        let notch = self
+            .cfg
+            .notch
+            .and_then(|n| n.placed(NotchPlace::Lid, top_flap.square.w, top_flap.square.h));
+        if let Some(notch) = notch {
+            self.result.append(
+                notch.draw_hole(offset.shift_xy(lid_len / Decimal::TWO, top_flap.square.h)),
+            );
+        }
 
         let top_flap_side_cut = SquareElement::new(self.cfg.glue_flap, top_flap.square.h)
             .with_borders(Borders::nope())
@@ -121,60 +398,102 @@ impl BoxCube {
 
         let offset = offset.shift_y(top_flap.square.h);
 
-        let lid_front_side = SquareElement::new(lid_len, self.cfg.lid_height).borders(
-            CutType::Nope,
-            CutType::Cut,
-            CutType::Bend,
-            CutType::Cut,
-        );
-
-        self.result.append(lid_front_side.draw(offset));
+        let lid_front_side = SquareElement::new(lid_len, self.cfg.lid_height)
+            .borders(CutType::Nope, CutType::Cut, CutType::Bend, CutType::Cut)
+            .role(Role::Wall);
 
-        let offset = offset.shift_y(lid_front_side.square.h);
-
-        let lid_top_wall = SquareElement::new(lid_len, lid_width).borders(
-            CutType::Nope,
-            CutType::Bend,
-            CutType::Bend,
-            CutType::Bend,
+        self.result.append(
+            lid_front_side
+                .named("lid-front")
+                .hinged("lid-top", Side::Bottom, 90.0)
+                .draw(offset),
         );
 
-        self.result.append(lid_top_wall.draw(offset));
+        let offset = offset.shift_y(lid_front_side.square.h);
 
-        let side_flap =
-            SquareElement::new(self.cfg.lid_height - self.cfg.thickness, self.cfg.glue_flap)
-                .borders(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut);
+        let lid_top_wall = SquareElement::new(lid_len, lid_width)
+            .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend)
+            .role(Role::Top);
 
         self.result.append(
-            side_flap.draw(
-                offset
-                    .shift_nx(self.cfg.thickness)
-                    .shift_y(self.cfg.thickness)
-                    .origin(Origin::BottomRight),
-            ),
+            lid_top_wall
+                .named("lid-top")
+                .hinged("back", Side::Bottom, 90.0)
+                .draw(offset),
         );
 
-        self.result.append(
-            side_flap.mirror_vertical().draw(
-                offset
-                    .shift_xy(lid_len + self.cfg.thickness, self.cfg.thickness)
-                    .origin(Origin::BottomLeft),
-            ),
+        let side_flap = self.cfg.flap_style.flap(
+            Square::new(self.cfg.lid_height - self.cfg.thickness, self.cfg.glue_flap),
+            Borders::new(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut),
+            Side::Bottom,
         );
 
+        if let Some(side_flap) = side_flap.map(|f| f.role(Role::GlueFlap)) {
+            self.result.append(
+                side_flap
+                    .named("lid-left-flap")
+                    .hinged_side("lid-left", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_nx(self.cfg.thickness)
+                            .shift_y(self.cfg.thickness)
+                            .origin(Origin::BottomRight),
+                    ),
+            );
+
+            self.result.append(
+                side_flap
+                    .mirror_vertical()
+                    .named("lid-right-flap")
+                    .hinged_side("lid-right", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_xy(lid_len + self.cfg.thickness, self.cfg.thickness)
+                            .origin(Origin::BottomLeft),
+                    ),
+            );
+        }
+
         let lid_side_wall = SquareElement::new(self.cfg.lid_height, lid_width - self.cfg.thickness)
-            .borders(CutType::Nope, CutType::Nope, CutType::Cut, CutType::Cut);
+            .borders(CutType::Nope, CutType::Nope, CutType::Cut, CutType::Cut)
+            .role(Role::Wall)
+            .hinged("lid-top", Side::Right, 90.0);
+
+        let lid_side_wall = if self.cfg.lid_fat {
+            let inner = InnerWalls {
+                wall: lid_side_wall.square,
+                gaps: (self.cfg.thickness, self.cfg.thickness),
+                thickness: self.cfg.thickness,
+            };
+            self.result.append(inner.draw(
+                offset.shift_y(self.cfg.thickness),
+                offset.shift_xy(lid_len, self.cfg.thickness),
+                [
+                    ("lid-left", "lid-left-inner"),
+                    ("lid-right", "lid-right-inner"),
+                ],
+            ));
+            lid_side_wall.border_left(CutType::Nope)
+        } else {
+            lid_side_wall
+        };
 
         self.result.append(
-            lid_side_wall.draw(offset.shift_y(self.cfg.thick_n(1)).origin(Origin::TopRight)),
+            lid_side_wall
+                .named("lid-left")
+                .draw(offset.shift_y(self.cfg.thick_n(1)).origin(Origin::TopRight)),
         );
 
         self.result.append(
             lid_side_wall
                 .mirror_vertical()
+                .named("lid-right")
                 .draw(offset.shift_xy(lid_len, self.cfg.thickness)),
         );
 
+        self.result.net.glue("lid-left-flap", "lid-front");
+        self.result.net.glue("lid-right-flap", "lid-front");
+
         // Small cut offs
         self.result
             .append(self.square_cut().draw(offset.origin(Origin::TopRight)));
@@ -186,17 +505,33 @@ impl BoxCube {
     }
 
     fn draw_main_walls(&mut self) {
-        let vertical_glue_flap = SquareElement::new(
-            self.cfg.glue_flap + self.cfg.thickness,
-            self.cfg.height - self.cfg.thick_n(2),
-        )
-        .borders(CutType::Cut, CutType::Nope, CutType::Cut, CutType::Cut);
-
+        let flap_h = self.cfg.height - self.cfg.thick_n(2);
+        let vertical_glue_flap = self
+            .cfg
+            .flap_style
+            .flap(
+                Square::new(self.cfg.glue_flap + self.cfg.thickness, flap_h),
+                Borders::new(CutType::Cut, CutType::Nope, CutType::Cut, CutType::Cut),
+                Side::Right,
+            )
+            .map(|f| f.role(Role::GlueFlap));
+
+        // Top edge is the hinge of the attached lid
+        let back_top = if self.cfg.attached_lid {
+            CutType::Nope
+        } else {
+            CutType::Cut
+        };
         let back_wall = SquareElement::new(self.cfg.length - self.cfg.thick_n(2), self.cfg.height)
-            .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend);
+            .borders(back_top, CutType::Bend, CutType::Bend, CutType::Bend)
+            .role(Role::Wall);
 
-        self.result
-            .append(back_wall.draw(self.offset.shift_x(self.cfg.thickness)));
+        self.result.append(
+            back_wall
+                .named("back")
+                .hinged("bottom", Side::Bottom, 90.0)
+                .draw(self.offset.shift_x(self.cfg.thickness)),
+        );
 
         self.result.append(
             self.square_cut_w().draw(
@@ -213,28 +548,32 @@ impl BoxCube {
             ),
         );
 
-        self.result.append(
-            vertical_glue_flap.draw(
-                self.offset
-                    .shift_xy(self.cfg.thickness, self.cfg.thickness)
-                    .origin(Origin::TopRight),
-            ),
-        );
-
-        self.result.append(
-            vertical_glue_flap.mirror_vertical().draw(
-                self.offset
-                    .shift_xy(self.cfg.thickness + back_wall.square.w, self.cfg.thickness),
-            ),
-        );
+        if let Some(flap) = &vertical_glue_flap {
+            self.result.append(
+                flap.named("back-left-flap")
+                    .hinged_side("back", Side::Right, 90.0)
+                    .draw(
+                        self.offset
+                            .shift_xy(self.cfg.thickness, self.cfg.thickness)
+                            .origin(Origin::TopRight),
+                    ),
+            );
+
+            self.result.append(
+                flap.mirror_vertical()
+                    .named("back-right-flap")
+                    .hinged_side("back", Side::Left, 90.0)
+                    .draw(
+                        self.offset
+                            .shift_xy(self.cfg.thickness + back_wall.square.w, self.cfg.thickness),
+                    ),
+            );
+        }
 
         self.result.append(
             self.square_cut_w().draw(
                 self.offset
-                    .shift_xy(
-                        self.cfg.thickness,
-                        vertical_glue_flap.square.h + self.cfg.thickness,
-                    )
+                    .shift_xy(self.cfg.thickness, flap_h + self.cfg.thickness)
                     .origin(Origin::TopRight),
             ),
         );
@@ -242,25 +581,30 @@ impl BoxCube {
         self.result
             .append(self.square_cut_w().draw(self.offset.shift_xy(
                 back_wall.square.w + self.cfg.thickness,
-                vertical_glue_flap.square.h + self.cfg.thickness,
+                flap_h + self.cfg.thickness,
             )));
 
         self.offset = self.offset.shift_y(back_wall.square.h);
 
-        let bottom_wall = SquareElement::new(self.cfg.length, self.cfg.width).borders(
-            CutType::Nope,
-            CutType::Bend,
-            CutType::Bend,
-            CutType::Bend,
-        );
+        let bottom_wall = SquareElement::new(self.cfg.length, self.cfg.width)
+            .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend)
+            .role(Role::Bottom);
 
-        self.result.append(bottom_wall.draw(self.offset));
+        self.result
+            .append(bottom_wall.named("bottom").draw(self.offset));
 
         self.draw_side_walls();
 
-        let front_wall = back_wall
+        let mut front_wall = back_wall
             .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut)
             .height(self.cfg.height - self.cfg.thickness);
+        let notch = self
+            .cfg
+            .notch
+            .and_then(|n| n.placed(NotchPlace::Front, front_wall.square.w, front_wall.square.h));
+        if let Some(notch) = notch {
+            front_wall = front_wall.notch(Side::Bottom, notch.radius);
+        }
 
         self.offset = self.offset.shift_y(bottom_wall.square.h);
 
@@ -268,6 +612,8 @@ impl BoxCube {
             front_wall
                 .border_left(CutType::Bend)
                 .border_right(CutType::Bend)
+                .named("front")
+                .hinged("bottom", Side::Top, 90.0)
                 .draw(self.offset.shift_x(self.cfg.thickness)),
         );
 
@@ -277,7 +623,13 @@ impl BoxCube {
             .shift_x(self.cfg.thickness)
             .origin(Origin::TopRight);
 
-        self.result.append(vertical_glue_flap.draw(offset_flap));
+        if let Some(flap) = &vertical_glue_flap {
+            self.result.append(
+                flap.named("front-left-flap")
+                    .hinged_side("front", Side::Right, 90.0)
+                    .draw(offset_flap),
+            );
+        }
 
         self.result.append(
             self.square_cut_w()
@@ -286,28 +638,84 @@ impl BoxCube {
 
         let offset_flap = offset_flap.shift_x(front_wall.square.w);
 
-        self.result.append(
-            vertical_glue_flap
-                .mirror_vertical()
-                .draw(offset_flap.origin(Origin::TopLeft)),
-        );
+        if let Some(flap) = &vertical_glue_flap {
+            self.result.append(
+                flap.mirror_vertical()
+                    .named("front-right-flap")
+                    .hinged_side("front", Side::Left, 90.0)
+                    .draw(offset_flap.origin(Origin::TopLeft)),
+            );
+        }
 
         self.result.append(
             self.square_cut_w()
                 .draw(offset_flap.origin(Origin::BottomLeft)),
         );
+
+        self.result.net.glue("back-left-flap", "left");
+        self.result.net.glue("back-right-flap", "right");
+        self.result.net.glue("front-left-flap", "left");
+        self.result.net.glue("front-right-flap", "right");
     }
 
     fn draw_side_walls(&mut self) {
         let wall = SquareElement::new(self.cfg.height - self.cfg.thickness, self.cfg.width)
-            .borders(CutType::Cut, CutType::Nope, CutType::Cut, CutType::Cut);
+            .borders(CutType::Cut, CutType::Nope, CutType::Cut, CutType::Cut)
+            .role(Role::Wall)
+            .hinged("bottom", Side::Right, 90.0);
 
-        self.result
-            .append(wall.draw(self.offset.origin(Origin::TopRight)));
+        self.result.append(
+            wall.named("left")
+                .draw(self.offset.origin(Origin::TopRight)),
+        );
 
         self.result.append(
             wall.mirror_vertical()
+                .named("right")
                 .draw(self.offset.shift_x(self.cfg.length)),
         );
+
+        self.draw_handles(wall.square.w);
+    }
+
+    /// Handle cut-outs of the side walls, `wall_height` is measured from the bottom bend
+    fn draw_handles(&mut self, wall_height: Decimal) {
+        let Some(handle) = self.cfg.handle else {
+            return;
+        };
+
+        // Glue flaps of the front and back walls cover ends of the side walls
+        let handle = Handle {
+            side_offset: handle
+                .side_offset
+                .max(self.cfg.glue_flap + self.cfg.thick_n(2)),
+            ..handle
+        };
+        // Lid covers the top of the walls
+        let top = self.cfg.handle_top.max(self.cfg.lid_height);
+
+        if self.cfg.width < handle.side_offset * Decimal::TWO + HANDLE_MIN_WIDTH {
+            log::warn!(
+                "Ручки не поместились между лепестками для склеивания, стенка слишком узкая"
+            );
+            return;
+        }
+        if top + handle.height > wall_height - self.cfg.thickness {
+            log::warn!("Ручки не поместились под крышкой, стенка слишком низкая");
+            return;
+        }
+
+        let (side_off, element) = handle.fit(self.cfg.width, false);
+
+        self.result
+            .append(element.draw(self.offset.shift_nx(wall_height - top).shift_y(side_off)));
+
+        self.result.append(
+            element.mirror_vertical().draw(
+                self.offset
+                    .shift_xy(self.cfg.length + wall_height - top, side_off)
+                    .origin(Origin::TopRight),
+            ),
+        );
     }
 }
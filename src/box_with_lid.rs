@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use rust_decimal::Decimal;
+
+use crate::box_cuboid;
+use crate::common::args::{
+    cli_help_arg, Clearance, GlueFlap, GlueFlapStyle, Height, Length, Thickness, Width,
+};
+use crate::common::{dec, file_name, DrawResult, Notch, NotchPlace};
+use crate::lid::{LidBorders, LidForBox, LidHeight};
+
+pub const CLI_SUBCOMMAND: &str = "box-with-lid";
+
+pub struct LidThickness;
+impl LidThickness {
+    const NAME: &'static str = "lidthickness";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("lid-thickness")
+            .value_parser(value_parser!(Decimal))
+            .help("Толщина картона крышки (мм), по умолчанию как у коробки.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub fn cli_build(root: Command) -> Command {
+    let c = Command::new(CLI_SUBCOMMAND)
+        .about("Коробка-параллелипипед и отдельная крышка к ней на одном листе.")
+        .arg(cli_help_arg())
+        .arg_required_else_help(true);
+
+    let c = box_cuboid::cli_args(c)
+        .arg(LidThickness::arg())
+        .arg(Clearance::arg());
+
+    root.subcommand(c)
+}
+
+pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
+    let clearance = Clearance::extract(m).unwrap();
+    if clearance < Decimal::ZERO {
+        bail!("Зазор не может быть отрицательным: {clearance}");
+    }
+    let thickness = Thickness::extract(m).unwrap();
+    let lid_thickness = LidThickness::extract(m).unwrap_or(thickness);
+    if lid_thickness <= Decimal::ZERO {
+        bail!("Толщина картона крышки должна быть больше 0: {lid_thickness}");
+    }
+
+    log::info!("Коробка с отдельной крышкой в работе.");
+
+    let mut result = box_cuboid::draw_base(m)?;
+    result.default_file_name = file_name(
+        CLI_SUBCOMMAND,
+        [
+            Length::extract(m).unwrap(),
+            Width::extract(m).unwrap(),
+            Height::extract(m).unwrap(),
+        ],
+        thickness,
+    );
+
+    // Lid goes around the outer size of the box, its own thickness is added outside
+    let gap = clearance * dec(2, 0);
+    let lid = LidForBox::separated(
+        Length::extract(m).unwrap() + gap,
+        Width::extract(m).unwrap() + gap,
+        LidHeight::extract(m).unwrap(),
+        lid_thickness,
+        GlueFlap::extract(m)?,
+        GlueFlapStyle::extract(m)?,
+    )
+    .fat(LidBorders::extract(m))
+    .notch(Notch::from_matches(m)?.filter(|n| n.place == NotchPlace::Lid));
+
+    // Box and lid could be cut from different boards
+    let mut lid = lid.draw_below(&result);
+    lid.net.prefix("lid-");
+    lid.net.keep_thickness();
+    result.net.keep_thickness();
+    result.append(lid);
+
+    Ok(result)
+}
@@ -0,0 +1,61 @@
+use anyhow::{bail, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::common::args::cli_help_arg;
+use crate::common::outline::check_outline;
+use crate::output;
+
+pub const CLI_SUBCOMMAND: &str = "check";
+
+pub struct Files;
+impl Files {
+    const NAME: &'static str = "files";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .required(true)
+            .value_name("SVG")
+            .action(ArgAction::Append)
+            .help("SVG файлы для проверки, созданные boxcut или любой другой программой.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Vec<String> {
+        m.get_many::<String>(Self::NAME)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn cli_build(root: Command) -> Command {
+    let c = Command::new(CLI_SUBCOMMAND)
+        .about("Проверить, что резы замкнуты и детали отделятся от листа.")
+        .arg(cli_help_arg())
+        .arg_required_else_help(true)
+        .arg(Files::arg());
+
+    root.subcommand(c)
+}
+
+pub fn cli_run(m: &ArgMatches) -> Result<()> {
+    let mut failed = 0;
+
+    for file in Files::extract(m) {
+        let content = std::fs::read_to_string(&file)?;
+        let issues = check_outline(&output::read_segments(&content)?);
+
+        if issues.is_empty() {
+            log::info!("{file}: все резы замкнуты");
+            continue;
+        }
+
+        failed += 1;
+        for issue in issues {
+            log::error!("{file}: {issue}");
+        }
+    }
+
+    if failed > 0 {
+        bail!("Проблемы с резами в файлах: {failed}");
+    }
+    Ok(())
+}
@@ -1,7 +1,11 @@
+use anyhow::{bail, Result};
 use clap::{command, value_parser, Arg, ArgAction, ArgMatches, Command};
 use rust_decimal::Decimal;
 use std::path::PathBuf;
 
+use crate::common::report::{Machine, ReportFormat};
+use crate::common::{FlapStyle, HandleStyle, NotchPlace};
+
 pub struct Length;
 impl Length {
     const NAME: &'static str = "length";
@@ -21,7 +25,7 @@ impl Length {
 
 pub struct Width;
 impl Width {
-    const NAME: &'static str = "width";
+    pub const NAME: &'static str = "width";
 
     pub fn arg() -> Arg {
         Arg::new(Self::NAME)
@@ -53,6 +57,25 @@ impl Height {
     }
 }
 
+pub struct Clearance;
+impl Clearance {
+    const NAME: &'static str = "clearance";
+
+    const DEFAULT: &'static str = "0.5";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("clearance")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Зазор с каждой стороны между коробкой и тем, что в нее вкладывается или надевается сверху (мм).")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
 pub struct GlueFlap;
 impl GlueFlap {
     const NAME: &'static str = "glueflap";
@@ -67,11 +90,185 @@ impl GlueFlap {
             .help("Длинна лепестка для склеивания (мм).")
     }
 
+    pub fn extract(m: &ArgMatches) -> Result<Decimal> {
+        let glue: Decimal = m.get_one(Self::NAME).copied().unwrap_or_default();
+        if glue <= Decimal::ZERO {
+            bail!("Длинна лепестка для склеивания должна быть больше 0: {glue}");
+        }
+        Ok(glue)
+    }
+}
+
+pub struct GlueFlapStyle;
+impl GlueFlapStyle {
+    const NAME: &'static str = "flapstyle";
+
+    const DEFAULT: &'static str = "square";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("flap-style")
+            .value_parser(["square", "chamfer", "trapezoid", "rounded"])
+            .default_value(Self::DEFAULT)
+            .help("Форма лепестков для склеивания: square - прямоугольник, chamfer - углы срезаны под 45°, trapezoid - трапеция с углом --flap-angle, rounded - скругленные углы.")
+    }
+
+    /// Style with the trapezoid angle from `GlueFlapAngle`
+    pub fn extract(m: &ArgMatches) -> Result<FlapStyle> {
+        let style = m
+            .get_one::<String>(Self::NAME)
+            .map(|v| v.as_str())
+            .unwrap_or(Self::DEFAULT);
+
+        Ok(match style {
+            "chamfer" => FlapStyle::Chamfer,
+            "trapezoid" => FlapStyle::Trapezoid(GlueFlapAngle::extract(m)?),
+            "rounded" => FlapStyle::Rounded,
+            _ => FlapStyle::Square,
+        })
+    }
+}
+
+pub struct GlueFlapAngle;
+impl GlueFlapAngle {
+    const NAME: &'static str = "flapangle";
+
+    const DEFAULT: &'static str = "60";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("flap-angle")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Угол между сгибом и боком лепестка-трапеции (градусы).")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Result<Decimal> {
+        let angle: Decimal = m.get_one(Self::NAME).copied().unwrap_or_default();
+        if angle <= Decimal::ZERO || angle > Decimal::from(90) {
+            bail!("Угол лепестка должен быть больше 0 и не больше 90 градусов: {angle}");
+        }
+        Ok(angle)
+    }
+}
+
+pub struct HandleShape;
+impl HandleShape {
+    const NAME: &'static str = "handle";
+
+    const DEFAULT: &'static str = "slot";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handle")
+            .value_parser(["slot", "d"])
+            .default_value(Self::DEFAULT)
+            .help("Форма ручки: slot - прорезь со скругленными концами, d - D-образный лепесток, загибается внутрь.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> HandleStyle {
+        match m.get_one::<String>(Self::NAME).map(|v| v.as_str()) {
+            Some("d") => HandleStyle::DFlap,
+            _ => HandleStyle::Slot,
+        }
+    }
+}
+
+pub struct HandleHeight;
+impl HandleHeight {
+    const NAME: &'static str = "handleheight";
+
+    const DEFAULT: &'static str = "25";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handle-height")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Высота ручки (мм).")
+    }
+
     pub fn extract(m: &ArgMatches) -> Option<Decimal> {
         m.get_one(Self::NAME).copied()
     }
 }
 
+pub struct HandleWidth;
+impl HandleWidth {
+    const NAME: &'static str = "handlewidth";
+
+    const DEFAULT: &'static str = "80";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handle-width")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Наибольшая ширина ручки (мм), на узкой стенке ручка будет уже.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct HandleOffset;
+impl HandleOffset {
+    const NAME: &'static str = "handleoffset";
+
+    const DEFAULT: &'static str = "25";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("handle-offset")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Наименьший отступ ручки от краев стенки (мм).")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct ThumbNotch;
+impl ThumbNotch {
+    const NAME: &'static str = "notch";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("notch")
+            .value_parser(value_parser!(Decimal))
+            .help("Радиус полукруглой выемки под палец, чтобы открывать крышку (мм). Без него выемки нет.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct NotchOn;
+impl NotchOn {
+    const NAME: &'static str = "notchon";
+
+    const DEFAULT: &'static str = "lid";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("notch-on")
+            .value_parser(["lid", "front"])
+            .default_value(Self::DEFAULT)
+            .help("Где вырезать выемку: lid - на юбке крышки, front - на передней стенке коробки.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> NotchPlace {
+        match m.get_one::<String>(Self::NAME).map(|v| v.as_str()) {
+            Some("front") => NotchPlace::Front,
+            _ => NotchPlace::Lid,
+        }
+    }
+}
+
 pub struct Thickness;
 impl Thickness {
     const NAME: &'static str = "thickness";
@@ -100,7 +297,7 @@ impl SaveFile {
             .short('f')
             .value_parser(value_parser!(PathBuf))
             .global(true)
-            .long_help("Имя/путь к .SVG файлу с результатом.\nЕсли не указано будет создан файл в текущей папке, существущий перезапишется.")
+            .long_help("Имя/путь к файлу с результатом (.svg, .dxf, .pdf, .obj, .stl или .json - список деталей), - чтобы вывести в консоль.\nЕсли не указано будет создан файл в текущей папке с моделью и размерами в имени, существущий перезапишется.\nВ имени можно использовать {model}, {date}, {material} и параметры модели, например {l}x{w}x{h}_t{t}.svg")
     }
 
     fn extract(m: &ArgMatches) -> Option<PathBuf> {
@@ -108,6 +305,155 @@ impl SaveFile {
     }
 }
 
+struct Material;
+impl Material {
+    const NAME: &'static str = "material";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("material")
+            .global(true)
+            .help("Материал, для {material} в имени файла.")
+    }
+
+    fn extract(m: &ArgMatches) -> Option<String> {
+        m.get_one::<String>(Self::NAME).cloned()
+    }
+}
+
+struct Report;
+impl Report {
+    const NAME: &'static str = "report";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("report")
+            .value_parser(["text", "json"])
+            .num_args(0..=1)
+            .default_missing_value("text")
+            .global(true)
+            .help("Отчет: площадь листа, длина резов и сгибов, время резки и стоимость. json выводится в консоль.")
+    }
+
+    fn extract(m: &ArgMatches) -> Option<ReportFormat> {
+        m.get_one::<String>(Self::NAME).map(|v| match v.as_str() {
+            "json" => ReportFormat::Json,
+            _ => ReportFormat::Text,
+        })
+    }
+}
+
+struct Manifest;
+impl Manifest {
+    const NAME: &'static str = "manifest";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("manifest")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Рядом с файлом записать .json со списком деталей: роли, размеры, положение на листе и площади лепестков.")
+    }
+
+    fn extract(m: &ArgMatches) -> bool {
+        m.get_flag(Self::NAME)
+    }
+}
+
+struct CutSpeed;
+impl CutSpeed {
+    const NAME: &'static str = "cutspeed";
+
+    const DEFAULT: &'static str = "20";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("cut-speed")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .global(true)
+            .help("Скорость резки насквозь для --report (мм/с).")
+    }
+
+    fn extract(m: &ArgMatches) -> Decimal {
+        m.get_one(Self::NAME).copied().unwrap_or_default()
+    }
+}
+
+struct BendSpeed;
+impl BendSpeed {
+    const NAME: &'static str = "bendspeed";
+
+    const DEFAULT: &'static str = "50";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("bend-speed")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .global(true)
+            .help("Скорость надреза линий сгиба для --report (мм/с).")
+    }
+
+    fn extract(m: &ArgMatches) -> Decimal {
+        m.get_one(Self::NAME).copied().unwrap_or_default()
+    }
+}
+
+struct PriceM2;
+impl PriceM2 {
+    const NAME: &'static str = "pricem2";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("price-m2")
+            .value_parser(value_parser!(Decimal))
+            .global(true)
+            .help("Цена квадратного метра картона, для стоимости в --report.")
+    }
+
+    fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+struct PriceMinute;
+impl PriceMinute {
+    const NAME: &'static str = "priceminute";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("price-minute")
+            .value_parser(value_parser!(Decimal))
+            .global(true)
+            .help("Цена минуты работы лазера, для стоимости в --report.")
+    }
+
+    fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+struct Precision;
+impl Precision {
+    const NAME: &'static str = "precision";
+
+    const DEFAULT: &'static str = "3";
+
+    fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("precision")
+            .value_parser(value_parser!(u32).range(0..=10))
+            .default_value(Self::DEFAULT)
+            .global(true)
+            .help("Знаков после запятой в координатах файла с результатом.")
+    }
+
+    fn extract(m: &ArgMatches) -> u32 {
+        m.get_one(Self::NAME).copied().unwrap_or_default()
+    }
+}
+
 pub fn cli_help_arg() -> Arg {
     Arg::new("help")
         .short('H')
@@ -125,17 +471,275 @@ pub fn cli_base_args() -> Command {
         .subcommand_help_heading("Типы моделей")
         .arg(cli_help_arg())
         .arg(SaveFile::arg())
+        .arg(Precision::arg())
+        .arg(Material::arg())
+        .arg(Report::arg())
+        .arg(Manifest::arg())
+        .arg(CutSpeed::arg())
+        .arg(BendSpeed::arg())
+        .arg(PriceM2::arg())
+        .arg(PriceMinute::arg())
 }
 
 #[derive(Debug, Clone)]
 pub struct ArgsGlobal {
     pub file: Option<String>,
+    /// Decimal places of coordinates in the output file
+    pub precision: u32,
+    pub material: Option<String>,
+    pub report: Option<ReportFormat>,
+    pub machine: Machine,
+    /// JSON list of panels next to the drawing
+    pub manifest: bool,
 }
 
 impl ArgsGlobal {
     pub fn from_matches(m: &ArgMatches) -> Self {
         Self {
             file: SaveFile::extract(m).map(|v| v.as_os_str().to_str().unwrap().to_string()),
+            precision: Precision::extract(m),
+            material: Material::extract(m),
+            report: Report::extract(m),
+            machine: Machine {
+                cut_speed: CutSpeed::extract(m),
+                bend_speed: BendSpeed::extract(m),
+                price_m2: PriceM2::extract(m),
+                price_minute: PriceMinute::extract(m),
+            },
+            manifest: Manifest::extract(m),
+        }
+    }
+
+    /// Laser speeds are divided by in `--report`
+    pub fn check_machine(&self) -> Result<()> {
+        if self.report.is_some()
+            && (self.machine.cut_speed <= Decimal::ZERO || self.machine.bend_speed <= Decimal::ZERO)
+        {
+            bail!("Скорость резки и сгибов должна быть больше 0");
+        }
+        Ok(())
+    }
+
+    /// Placeholders of the file name which are not model parameters
+    pub fn file_vars(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("date", date_today()),
+            ("material", self.material.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Current date as YYYY-MM-DD (UTC)
+fn date_today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (y, m, d) = civil_date((secs / 86400) as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Year, month and day of the date `days` after 1970-01-01
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    // Years of 400 days cycles starting from March, so leap day is the last one
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Model (subcommand) name with raw values of its arguments.
+/// Allows to run a model without a terminal (server, regeneration from file, etc).
+#[derive(Debug, Clone, Default)]
+pub struct ModelParams {
+    pub model: String,
+    pub values: Vec<(String, String)>,
+}
+
+impl ModelParams {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            values: Vec::new(),
+        }
+    }
+
+    /// All values of the model arguments including defaults.
+    /// `cmd` is the model subcommand.
+    pub fn from_matches(cmd: &Command, m: &ArgMatches) -> Self {
+        let mut params = Self::new(cmd.get_name());
+
+        for arg in cmd.get_arguments() {
+            let id = arg.get_id().as_str();
+            if arg.is_global_set() || !matches!(m.try_contains_id(id), Ok(true)) {
+                continue;
+            }
+
+            if let Some(raw) = m.get_raw(id) {
+                let values: Vec<_> = raw.map(|v| v.to_string_lossy().to_string()).collect();
+                match values.first() {
+                    Some(v) => params.set(id, v),
+                    // Switch without value
+                    None => params.set(id, "true"),
+                }
+            }
+        }
+
+        params
+    }
+
+    /// Set parameter value, replacing previous one with the same name
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some(v) => v.1 = value.to_string(),
+            None => self.values.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Subcommand of the model, other subcommands like `serve` are not models
+    fn command<'a>(&self, root: &'a Command) -> Result<&'a Command> {
+        match root.find_subcommand(&self.model) {
+            Some(cmd) if crate::MODELS.contains(&self.model.as_str()) => Ok(cmd),
+            _ => bail!("Неизвестная модель: {}", self.model),
+        }
+    }
+
+    /// Command line for the model subcommand of the `root` command.
+    /// Parameter could be named by argument id, long or short name.
+    pub fn to_argv(&self, root: &Command) -> Result<Vec<String>> {
+        let cmd = self.command(root)?;
+
+        // Same argument could be named differently, last value wins
+        let mut resolved: Vec<(&Arg, &String)> = Vec::new();
+        for (name, value) in self.values.iter() {
+            let Some(arg) = find_arg(cmd, name) else {
+                bail!("Неизвестный параметр {} для модели {}", name, self.model);
+            };
+
+            match resolved
+                .iter_mut()
+                .find(|(a, _)| a.get_id() == arg.get_id())
+            {
+                Some(v) => v.1 = value,
+                None => resolved.push((arg, value)),
+            }
+        }
+
+        let mut argv = vec![root.get_name().to_string(), self.model.clone()];
+
+        for (arg, value) in resolved {
+            let flag = match (arg.get_long(), arg.get_short()) {
+                (Some(long), _) => format!("--{long}"),
+                (None, Some(short)) => format!("-{short}"),
+                (None, None) => bail!(
+                    "Параметр {} модели {} нельзя задать по имени",
+                    arg.get_id(),
+                    self.model
+                ),
+            };
+
+            if is_flag(arg) {
+                if value_is_true(value) {
+                    argv.push(flag);
+                }
+            } else {
+                argv.push(flag);
+                argv.push(value.to_string());
+            }
         }
+
+        Ok(argv)
+    }
+
+    /// Value of the parameter named by argument id, long or short name
+    pub fn get(&self, root: &Command, name: &str) -> Result<Option<&str>> {
+        let cmd = self.command(root)?;
+        let Some(arg) = find_arg(cmd, name) else {
+            bail!("Неизвестный параметр {} для модели {}", name, self.model);
+        };
+
+        Ok(self
+            .values
+            .iter()
+            .rev()
+            .find(|(n, _)| find_arg(cmd, n).is_some_and(|a| a.get_id() == arg.get_id()))
+            .map(|(_, v)| v.as_str()))
+    }
+
+    /// Replace `{model}`, `{parameter}` and `vars` placeholders in the template
+    pub fn fill(&self, root: &Command, template: &str, vars: &[(&str, String)]) -> Result<String> {
+        let mut result = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                bail!("Нет закрывающей скобки в шаблоне: {template}");
+            };
+            result.push_str(&rest[..start]);
+            let name = &rest[start + 1..start + len];
+            if name == "model" {
+                result.push_str(&self.model);
+            } else if let Some((_, value)) = vars.iter().find(|(n, _)| *n == name) {
+                result.push_str(value);
+            } else {
+                result.push_str(self.get(root, name)?.unwrap_or_default());
+            }
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+
+        Ok(result)
+    }
+}
+
+/// Find argument by id, long or short name
+pub fn find_arg<'a>(cmd: &'a Command, name: &str) -> Option<&'a Arg> {
+    cmd.get_arguments().find(|a| {
+        a.get_id().as_str() == name
+            || a.get_long() == Some(name)
+            || a.get_short().map(|s| s.to_string()).as_deref() == Some(name)
+    })
+}
+
+/// Argument without value (switch)
+pub fn is_flag(arg: &Arg) -> bool {
+    !arg.get_action().takes_values() || arg.get_num_args().is_some_and(|n| n.max_values() == 0)
+}
+
+fn value_is_true(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "" | "1" | "true" | "on" | "yes"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(59), (1970, 3, 1));
+        assert_eq!(civil_date(11016), (2000, 2, 29));
+        assert_eq!(civil_date(20744), (2026, 10, 18));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn positional_argument_is_an_error() {
+        let root = Command::new("boxcut").subcommand(Command::new("lid").arg(Arg::new("source")));
+        let mut params = ModelParams::new("lid");
+        params.set("source", "x");
+        assert!(params.to_argv(&root).is_err());
+
+        let mut params = ModelParams::new("regen");
+        params.set("source", "x");
+        assert!(params.to_argv(&crate::cli_build()).is_err());
     }
 }
@@ -0,0 +1,130 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::common::{Borders, CutType, EdgeShape, Point, PolygonElement, Side, Square};
+
+/// Position along the base and depth from the free side
+type Uv = (Decimal, Decimal);
+
+/// Shape of the glue flap corners away from the fold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlapStyle {
+    /// Plain rectangle
+    Square,
+    /// Corners cut at 45° by the half of the flap depth
+    Chamfer,
+    /// Sides sloped by the angle (degrees) to the fold line
+    Trapezoid(Decimal),
+    /// Corners rounded by the flap depth
+    Rounded,
+}
+
+impl FlapStyle {
+    /// Flap of the `square` size attached to the parent by the `base` side.
+    /// Side opposite to the base is free, corners are shaped at its ends.
+    /// None when the flap has no area.
+    pub fn flap(&self, square: Square, borders: Borders, base: Side) -> Option<PolygonElement> {
+        if square.w <= Decimal::ZERO || square.h <= Decimal::ZERO {
+            return None;
+        }
+
+        let (along, depth) = match base {
+            Side::Top | Side::Bottom => (square.w, square.h),
+            Side::Left | Side::Right => (square.h, square.w),
+        };
+
+        // Edges going clockwise from the free one
+        let sides = [borders.top, borders.right, borders.bottom, borders.left];
+        let b = base.edge();
+        let (free, right, base_tp, left) = (
+            sides[(b + 2) % 4],
+            sides[(b + 3) % 4],
+            sides[b],
+            sides[(b + 1) % 4],
+        );
+        let corner = |lateral: CutType| match (free, lateral) {
+            (CutType::Cut, _) | (_, CutType::Cut) => CutType::Cut,
+            _ => lateral,
+        };
+
+        let half = along / Decimal::TWO;
+        // Outline with the base at the bottom: vertex, edge type and arc center
+        let outline: Vec<(Uv, CutType, Option<Uv>)> = match self {
+            FlapStyle::Square => vec![
+                ((Decimal::ZERO, Decimal::ZERO), free, None),
+                ((along, Decimal::ZERO), right, None),
+                ((along, depth), base_tp, None),
+                ((Decimal::ZERO, depth), left, None),
+            ],
+            FlapStyle::Trapezoid(angle) => {
+                let tan = angle.to_f64().unwrap_or(90.0).to_radians().tan();
+                let inset = Decimal::from_f64(depth.to_f64().unwrap_or_default() / tan)
+                    .unwrap_or_default()
+                    .max(Decimal::ZERO)
+                    .min(half);
+                vec![
+                    ((inset, Decimal::ZERO), free, None),
+                    ((along - inset, Decimal::ZERO), right, None),
+                    ((along, depth), base_tp, None),
+                    ((Decimal::ZERO, depth), left, None),
+                ]
+            }
+            FlapStyle::Chamfer => {
+                let c = depth.min(half) / Decimal::TWO;
+                vec![
+                    ((c, Decimal::ZERO), free, None),
+                    ((along - c, Decimal::ZERO), corner(right), None),
+                    ((along, c), right, None),
+                    ((along, depth), base_tp, None),
+                    ((Decimal::ZERO, depth), left, None),
+                    ((Decimal::ZERO, c), corner(left), None),
+                ]
+            }
+            FlapStyle::Rounded => {
+                let r = depth.min(half);
+                vec![
+                    ((r, Decimal::ZERO), free, None),
+                    (
+                        (along - r, Decimal::ZERO),
+                        corner(right),
+                        Some((along - r, r)),
+                    ),
+                    ((along, r), right, None),
+                    ((along, depth), base_tp, None),
+                    ((Decimal::ZERO, depth), left, None),
+                    ((Decimal::ZERO, r), corner(left), Some((r, r))),
+                ]
+            }
+        };
+
+        // Rotate the outline to put the base on its side
+        let place = |(u, v): Uv| match base {
+            Side::Bottom => Point::new(u, v),
+            Side::Top => Point::new(along - u, depth - v),
+            Side::Right => Point::new(v, along - u),
+            Side::Left => Point::new(depth - v, u),
+        };
+
+        // Edges of zero length are left out, like the free side of a triangle
+        let n = outline.len();
+        let outline: Vec<_> = (0..n)
+            .filter(|i| outline[*i].0 != outline[(i + 1) % n].0)
+            .map(|i| outline[i])
+            .collect();
+
+        let vertices: Vec<Point> = outline.iter().map(|(p, _, _)| place(*p)).collect();
+        let edges: Vec<CutType> = outline.iter().map(|(_, tp, _)| *tp).collect();
+        let shapes: Vec<EdgeShape> = outline
+            .iter()
+            .map(|(_, _, center)| match center {
+                Some(c) => EdgeShape::Arc {
+                    center: place(*c),
+                    clockwise: true,
+                },
+                None => EdgeShape::Straight,
+            })
+            .collect();
+
+        PolygonElement::new(&vertices, &edges).map(|p| p.with_shapes(&shapes))
+    }
+}
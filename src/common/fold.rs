@@ -0,0 +1,244 @@
+//! Virtual folding of the box net into 3D.
+//! Coordinates are sheet millimeters: X to the right, Y down the sheet, Z up from the sheet.
+use std::collections::HashMap;
+use std::ops::{Add, Mul, Sub};
+
+use anyhow::{bail, Result};
+
+use super::{Net, Panel, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn on_sheet(p: Point) -> Self {
+        let (x, y) = p.to_f64();
+        Self::new(x, y, 0.0)
+    }
+
+    pub fn dot(&self, other: Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn len(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Vec3 {
+        let len = self.len();
+        if len == 0.0 {
+            *self
+        } else {
+            *self * (1.0 / len)
+        }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+    fn add(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x + o.x, self.y + o.y, self.z + o.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+    fn sub(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Vec3;
+    fn mul(self, k: f64) -> Vec3 {
+        Vec3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+/// Affine transformation: rotation matrix and translation
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    rot: [[f64; 3]; 3],
+    shift: Vec3,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            rot: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            shift: Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Rotation by `angle` degrees around the hinge line going through `at` along `axis`.
+    /// `towards` is the in-sheet direction from the hinge to the rotated panel,
+    /// positive angle lifts it up from the sheet.
+    pub fn hinge(at: Vec3, axis: Vec3, towards: Vec3, angle: f64) -> Self {
+        let (s, c) = angle.to_radians().sin_cos();
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let from = [axis, towards, up];
+        let to = [axis, towards * c + up * s, up * c - towards * s];
+
+        let mut rot = [[0.0; 3]; 3];
+        for (i, row) in rot.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| coord(to[k], i) * coord(from[k], j)).sum();
+            }
+        }
+
+        let rotation = Self {
+            rot,
+            shift: Vec3::new(0.0, 0.0, 0.0),
+        };
+        Self {
+            rot,
+            shift: at - rotation.rotate(at),
+        }
+    }
+
+    fn rotate(&self, v: Vec3) -> Vec3 {
+        let r = &self.rot;
+        Vec3::new(
+            r[0][0] * v.x + r[0][1] * v.y + r[0][2] * v.z,
+            r[1][0] * v.x + r[1][1] * v.y + r[1][2] * v.z,
+            r[2][0] * v.x + r[2][1] * v.y + r[2][2] * v.z,
+        )
+    }
+
+    pub fn apply(&self, p: Vec3) -> Vec3 {
+        self.rotate(p) + self.shift
+    }
+
+    /// Direction vectors are not affected by translation
+    pub fn apply_dir(&self, v: Vec3) -> Vec3 {
+        self.rotate(v)
+    }
+
+    /// Apply `inner` first and then self
+    pub fn compose(&self, inner: &Transform) -> Self {
+        let mut rot = [[0.0; 3]; 3];
+        for (i, row) in rot.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| self.rot[i][k] * inner.rot[k][j]).sum();
+            }
+        }
+
+        Self {
+            rot,
+            shift: self.apply(inner.shift),
+        }
+    }
+}
+
+fn coord(v: Vec3, i: usize) -> f64 {
+    match i {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+/// Panel placed into the folded box
+#[derive(Debug, Clone)]
+pub struct FoldedPanel<'a> {
+    pub panel: &'a Panel,
+    pub transform: Transform,
+}
+
+impl FoldedPanel<'_> {
+    /// Outline of the panel in the folded box, same order as [`Panel::vertices`]
+    pub fn vertices(&self) -> Vec<Vec3> {
+        self.panel
+            .vertices
+            .iter()
+            .map(|v| self.transform.apply(Vec3::on_sheet(*v)))
+            .collect()
+    }
+
+    /// Direction of the panel side which was looking up from the sheet
+    pub fn normal(&self) -> Vec3 {
+        self.transform.apply_dir(Vec3::new(0.0, 0.0, 1.0))
+    }
+}
+
+/// Hinge rotation of the panel relative to the flat parent
+fn hinge_transform(panel: &Panel, edge: usize, angle: f64) -> Transform {
+    let (from, to) = panel.edge(edge);
+    let (from, to) = (Vec3::on_sheet(from), Vec3::on_sheet(to));
+    let axis = (to - from).normalize();
+    // Outline goes clockwise, so the panel is on the right side of the edge
+    let towards = Vec3::new(-axis.y, axis.x, 0.0);
+
+    Transform::hinge(from, axis, towards, angle)
+}
+
+/// Fold named panels of the net around hinges, panels without hinges stay on the sheet
+pub fn fold(net: &Net) -> Result<Vec<FoldedPanel<'_>>> {
+    let named: Vec<(&str, &Panel)> = net
+        .panels
+        .iter()
+        .filter_map(|p| p.name.as_deref().map(|n| (n, p)))
+        .collect();
+
+    for (i, (name, _)) in named.iter().enumerate() {
+        if named[..i].iter().any(|(n, _)| n == name) {
+            bail!("Две панели с одинаковым именем {}", name);
+        }
+    }
+
+    let mut done: HashMap<&str, Transform> = HashMap::new();
+
+    // Parents could be declared after children, so resolve in passes
+    while done.len() < named.len() {
+        let before = done.len();
+
+        for (name, panel) in named.iter() {
+            if done.contains_key(name) {
+                continue;
+            }
+
+            match panel.hinge.as_ref() {
+                None => {
+                    done.insert(name, Transform::identity());
+                }
+                Some(h) => {
+                    if net.panel(&h.parent).is_none() {
+                        bail!("Панель {} прикреплена к несуществующей {}", name, h.parent);
+                    }
+                    if let Some(parent) = done.get(h.parent.as_str()) {
+                        let t = parent.compose(&hinge_transform(panel, h.edge, h.angle));
+                        done.insert(name, t);
+                    }
+                }
+            }
+        }
+
+        if done.len() == before {
+            bail!("Панели прикреплены друг к другу по кругу");
+        }
+    }
+
+    Ok(named
+        .into_iter()
+        .map(|(name, panel)| FoldedPanel {
+            panel,
+            transform: done[name],
+        })
+        .collect())
+}
@@ -0,0 +1,129 @@
+use anyhow::{bail, Result};
+use clap::ArgMatches;
+use rust_decimal::Decimal;
+
+use super::args::{HandleHeight, HandleOffset, HandleShape, HandleWidth};
+use crate::common::{
+    dec, Borders, CutType, DrawResult, FlapStyle, Point, PolygonElement, Role, Side, Square,
+    SquareElement,
+};
+
+/// Narrower handle holes are useless
+pub const HANDLE_MIN_WIDTH: Decimal = dec(10, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleStyle {
+    /// Hole with round ends (stadium)
+    Slot,
+    /// D-shaped cut, the flat side is a bend and the piece folds inside
+    DFlap,
+}
+
+/// Handle cut-out settings
+#[derive(Debug, Clone, Copy)]
+pub struct Handle {
+    pub style: HandleStyle,
+    pub height: Decimal,
+    pub max_width: Decimal,
+    /// Minimal distance from the wall sides
+    pub side_offset: Decimal,
+}
+
+impl Handle {
+    pub fn from_matches(m: &ArgMatches) -> Result<Self> {
+        let handle = Self {
+            style: HandleShape::extract(m),
+            height: HandleHeight::extract(m).unwrap(),
+            max_width: HandleWidth::extract(m).unwrap(),
+            side_offset: HandleOffset::extract(m).unwrap(),
+        };
+
+        if handle.height <= Decimal::ZERO {
+            bail!("Высота ручки должна быть больше 0: {}", handle.height);
+        }
+        if handle.max_width < HANDLE_MIN_WIDTH {
+            bail!(
+                "Ширина ручки должна быть не меньше {HANDLE_MIN_WIDTH}мм: {}",
+                handle.max_width
+            );
+        }
+        if handle.side_offset < Decimal::ZERO {
+            bail!(
+                "Отступ ручки не может быть отрицательным: {}",
+                handle.side_offset
+            );
+        }
+
+        Ok(handle)
+    }
+
+    /// Handle across the wall of `wall_width`: distance from the wall side and the cut-out.
+    /// The cut-out lies along the wall when `horizontal`, otherwise it is turned across.
+    /// On a narrow wall the side offset shrinks, the wall without room gets an empty mark.
+    pub fn fit(&self, wall_width: Decimal, horizontal: bool) -> (Decimal, HandleElement) {
+        let width = (wall_width - self.side_offset * Decimal::TWO)
+            .min(self.max_width)
+            .max(HANDLE_MIN_WIDTH);
+
+        if width >= wall_width {
+            let mark = SquareElement::new(HANDLE_MIN_WIDTH, HANDLE_MIN_WIDTH);
+            return (Decimal::ZERO, HandleElement::Hole(mark));
+        }
+
+        let side_offset = (wall_width - width) / Decimal::TWO;
+
+        let (square, base) = if horizontal {
+            (Square::new(width, self.height), Side::Top)
+        } else {
+            (Square::new(self.height, width), Side::Left)
+        };
+
+        let element = match self.style {
+            HandleStyle::Slot => HandleElement::Hole(
+                SquareElement::cut(square.w, square.h)
+                    .role(Role::Hole)
+                    .rounded(self.height / Decimal::TWO),
+            ),
+            HandleStyle::DFlap => {
+                let mut borders = Borders::new_cut();
+                match base {
+                    Side::Top => borders.top = CutType::Bend,
+                    _ => borders.left = CutType::Bend,
+                }
+                HandleElement::Flap(
+                    FlapStyle::Rounded
+                        .flap(square, borders, base)
+                        .expect("Handle size is checked in from_matches")
+                        .role(Role::Flap),
+                )
+            }
+        };
+
+        (side_offset, element)
+    }
+}
+
+/// Cut-out of the handle
+#[derive(Debug, Clone)]
+pub enum HandleElement {
+    Hole(SquareElement),
+    /// Stays attached to the wall, it is folded inside by hand
+    Flap(PolygonElement),
+}
+
+impl HandleElement {
+    /// Flap is folded to the other side
+    pub fn mirror_vertical(&self) -> Self {
+        match self {
+            HandleElement::Hole(e) => HandleElement::Hole(e.mirror_vertical()),
+            HandleElement::Flap(e) => HandleElement::Flap(e.mirror_vertical()),
+        }
+    }
+
+    pub fn draw(&self, offset: Point) -> DrawResult {
+        match self {
+            HandleElement::Hole(e) => e.draw(offset),
+            HandleElement::Flap(e) => e.draw(offset),
+        }
+    }
+}
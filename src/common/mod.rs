@@ -1,46 +1,94 @@
 use core::option::Option;
 
-use svg::node::element::path::{Data, Parameters};
-use svg::node::element::Path;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
 
 pub mod args;
+mod flap;
+pub mod fold;
+mod handle;
+mod net;
+mod notch;
+pub mod outline;
+mod polygon;
+pub mod report;
+mod segment;
 mod square;
-
+pub mod validate;
+
+use args::ModelParams;
+pub use flap::*;
+pub use handle::*;
+pub use net::*;
+pub use notch::*;
+pub use polygon::*;
+pub use segment::*;
 pub use square::*;
 
-pub const VIEWPORT_OFFSET: f64 = 5.0;
+pub const VIEWPORT_OFFSET: Decimal = dec(5, 0);
+
+/// Decimal constant `num / 10^scale`, e.g. `dec(23, 1)` is 2.3
+pub const fn dec(num: u32, scale: u32) -> Decimal {
+    Decimal::from_parts(num, 0, 0, false, scale)
+}
 
 // pub const DEFAULT_THICKNESS: f64 = 2.0;
 
 pub const DEFAULT_FILE_NAME: &str = "LaserCutBox.svg";
 
+/// File name with the model, outer sizes and board thickness, e.g. `box-cuboid_300x200x100_t2.3.svg`
+pub fn file_name(model: &str, [l, w, h]: [Decimal; 3], thickness: Decimal) -> String {
+    format!(
+        "{model}_{}x{}x{}_t{}.svg",
+        l.normalize(),
+        w.normalize(),
+        h.normalize(),
+        thickness.normalize()
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct DrawResult {
     pub default_file_name: String,
-    pub paths: Vec<Path>,
+    /// Panels of the drawing, turned into segments only on output
+    pub net: Net,
     pub max: Point,
+    /// Model parameters used to draw it
+    pub params: Option<ModelParams>,
 }
 
 impl DrawResult {
     pub fn empty(default_file_name: String) -> Self {
         Self {
             default_file_name,
-            paths: Vec::new(),
-            max: Point::new(0.0, 0.0),
+            net: Net::default(),
+            max: Point::zero(),
+            params: None,
         }
     }
 
-    pub fn new(paths: Vec<Path>, max: Point) -> Self {
+    pub fn new(net: Net, max: Point) -> Self {
         Self {
             default_file_name: DEFAULT_FILE_NAME.into(),
-            paths,
+            net,
             max,
+            params: None,
         }
     }
 
     pub fn append(&mut self, other: DrawResult) {
-        self.paths.extend(other.paths);
-        self.max.update_max(other.max)
+        self.max.update_max(other.max);
+        self.net.append(other.net);
+    }
+
+    /// Cut and bend segments of the drawing
+    pub fn segments(&self) -> Vec<Segment> {
+        self.net.segments()
+    }
+
+    /// Problems with cut outlines, empty when every part separates from the sheet
+    pub fn outline_issues(&self) -> Vec<outline::OutlineIssue> {
+        outline::check_outline(&self.segments())
     }
 }
 
@@ -56,13 +104,13 @@ pub enum Origin {
 
 #[derive(Debug, Clone, Copy)]
 pub struct Point {
-    pub x: f64,
-    pub y: f64,
+    pub x: Decimal,
+    pub y: Decimal,
     pub origin: Origin,
 }
 
 impl Point {
-    pub fn new(x: f64, y: f64) -> Self {
+    pub fn new(x: Decimal, y: Decimal) -> Self {
         Self {
             x,
             y,
@@ -70,6 +118,26 @@ impl Point {
         }
     }
 
+    pub fn zero() -> Self {
+        Self::new(Decimal::ZERO, Decimal::ZERO)
+    }
+
+    /// Point from coordinates which are not exact, like parsed from a file
+    pub fn from_f64(x: f64, y: f64) -> Self {
+        Self::new(
+            Decimal::from_f64(x).unwrap_or_default(),
+            Decimal::from_f64(y).unwrap_or_default(),
+        )
+    }
+
+    /// Coordinates for the approximate math (3D, distances)
+    pub fn to_f64(self) -> (f64, f64) {
+        (
+            self.x.to_f64().unwrap_or_default(),
+            self.y.to_f64().unwrap_or_default(),
+        )
+    }
+
     /// Add input offset to the current one
     /// Return new struct
     pub fn shift_with(&self, delta: Self) -> Self {
@@ -81,7 +149,7 @@ impl Point {
     }
 
     /// Return new offset with adjustment
-    pub fn shift_xy(&self, delta_x: f64, delta_y: f64) -> Self {
+    pub fn shift_xy(&self, delta_x: Decimal, delta_y: Decimal) -> Self {
         Self {
             x: self.x + delta_x,
             y: self.y + delta_y,
@@ -89,7 +157,7 @@ impl Point {
         }
     }
 
-    pub fn shift_x(&self, delta_x: f64) -> Self {
+    pub fn shift_x(&self, delta_x: Decimal) -> Self {
         Self {
             x: self.x + delta_x,
             y: self.y,
@@ -97,11 +165,11 @@ impl Point {
         }
     }
 
-    pub fn shift_nx(&self, x: f64) -> Self {
-        self.shift_x(x * -1.0)
+    pub fn shift_nx(&self, x: Decimal) -> Self {
+        self.shift_x(-x)
     }
 
-    pub fn shift_y(&self, delta_y: f64) -> Self {
+    pub fn shift_y(&self, delta_y: Decimal) -> Self {
         Self {
             x: self.x,
             y: self.y + delta_y,
@@ -109,8 +177,8 @@ impl Point {
         }
     }
 
-    pub fn shift_ny(&self, y: f64) -> Self {
-        self.shift_y(y * -1.0)
+    pub fn shift_ny(&self, y: Decimal) -> Self {
+        self.shift_y(-y)
     }
 
     pub fn origin(&self, origin: Origin) -> Self {
@@ -124,12 +192,10 @@ impl Point {
     /// Align point to TopLeft position related to Suare
     pub fn align_top_left(&self, square: Square) -> Self {
         match self.origin {
-            Origin::TopLeft => self.clone(),
-            Origin::TopRight => self.shift_x(square.w * -1.0).origin(Origin::TopLeft),
-            Origin::BottomRight => self
-                .shift_xy(square.w * -1.0, square.h * -1.0)
-                .origin(Origin::TopLeft),
-            Origin::BottomLeft => self.shift_y(square.h * -1.0).origin(Origin::TopLeft),
+            Origin::TopLeft => *self,
+            Origin::TopRight => self.shift_x(-square.w).origin(Origin::TopLeft),
+            Origin::BottomRight => self.shift_xy(-square.w, -square.h).origin(Origin::TopLeft),
+            Origin::BottomLeft => self.shift_y(-square.h).origin(Origin::TopLeft),
         }
     }
 
@@ -143,26 +209,10 @@ impl Point {
             self.y = input.y;
         }
     }
-
-    pub fn as_parameters(&self) -> Parameters {
-        Parameters::from(self)
-    }
-}
-
-impl From<Point> for Parameters {
-    fn from(value: Point) -> Self {
-        Parameters::from((value.x, value.y))
-    }
-}
-
-impl From<&Point> for Parameters {
-    fn from(value: &Point) -> Self {
-        Parameters::from((value.x, value.y))
-    }
 }
 
 /// Defines laser cuting type
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CutType {
     /// Do not cut - do not draw
     Nope,
@@ -181,36 +231,18 @@ impl CutType {
     }
 }
 
-fn path_for(tp: &CutType, data: Data) -> Path {
-    let mut stroke = "white";
-    let width = 0.2;
-    match tp {
-        CutType::Nope => {}
-        CutType::Cut => {
-            stroke = "black";
-            // width = 0.1;
-        }
-        CutType::Bend => {
-            stroke = "green";
-            // width = 0.1;
-        }
-    }
-
-    Path::new()
-        .set("fill", "none")
-        .set("stroke", stroke)
-        .set("stroke-width", width)
-        .set("d", data)
+/// Straight segment of the drawing
+#[derive(Debug, Clone, Copy)]
+pub struct Line {
+    pub from: Point,
+    pub to: Point,
+    pub tp: CutType,
 }
 
-pub fn draw_line(from: Point, to: Point, tp: &CutType) -> Option<Path> {
+pub fn draw_line(from: Point, to: Point, tp: &CutType) -> Option<Line> {
     if !tp.is_cuttable() {
         return None;
     }
 
-    let data = Data::new()
-        .move_to(from.as_parameters())
-        .line_to(to.as_parameters());
-
-    Some(path_for(tp, data))
+    Some(Line { from, to, tp: *tp })
 }
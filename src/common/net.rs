@@ -0,0 +1,416 @@
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use super::{draw_arc, draw_line, Arc, Borders, CutType, Point, Segment, Square};
+
+/// Side of a rectangular panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Side {
+    /// Index of the rectangle edge, edges go clockwise from the top one
+    pub fn edge(&self) -> usize {
+        match self {
+            Side::Top => 0,
+            Side::Right => 1,
+            Side::Bottom => 2,
+            Side::Left => 3,
+        }
+    }
+}
+
+/// Purpose of the panel in the box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Only marks cut lines, has no body
+    Mark,
+    Bottom,
+    Wall,
+    /// Top of the lid
+    Top,
+    /// Folding flap: tucked in or doubling a wall
+    Flap,
+    /// Flap glued to another panel
+    GlueFlap,
+    /// Small piece removed from the sheet
+    CutOff,
+    /// Hole cut in another panel
+    Hole,
+    /// Separate part which is not folded with the box
+    Insert,
+}
+
+impl Role {
+    /// Panel is a part of the folded box
+    pub fn is_folded(&self) -> bool {
+        matches!(
+            self,
+            Role::Bottom | Role::Wall | Role::Top | Role::Flap | Role::GlueFlap
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Mark => "mark",
+            Role::Bottom => "bottom",
+            Role::Wall => "wall",
+            Role::Top => "top",
+            Role::Flap => "flap",
+            Role::GlueFlap => "glue-flap",
+            Role::CutOff => "cut-off",
+            Role::Hole => "hole",
+            Role::Insert => "insert",
+        }
+    }
+}
+
+/// Folding connection of the panel to its parent panel
+#[derive(Debug, Clone)]
+pub struct Hinge {
+    pub parent: String,
+    /// Edge of the panel attached to the parent
+    pub edge: usize,
+    /// Fold angle (degrees), positive angle folds panel up from the sheet
+    pub angle: f64,
+}
+
+/// Shape of the panel edge between its end vertices
+#[derive(Debug, Clone, Copy)]
+pub enum EdgeShape {
+    Straight,
+    /// Circular arc around the center
+    Arc {
+        center: Point,
+        clockwise: bool,
+    },
+}
+
+/// Flat part of the box net
+#[derive(Debug, Clone)]
+pub struct Panel {
+    /// Only panels with names could be referenced by hinges and glue pairs
+    pub name: Option<String>,
+    pub role: Role,
+    /// Outline clockwise on the sheet
+    pub vertices: Vec<Point>,
+    /// Cut type of the edge from the vertex with the same index to the next one
+    pub edges: Vec<CutType>,
+    /// Shape of the edge with the same index, folding treats every edge as straight
+    pub shapes: Vec<EdgeShape>,
+    /// None for panels which stay on the sheet
+    pub hinge: Option<Hinge>,
+    /// Board thickness when the panel is cut from the other board than the net
+    pub thickness: Option<Decimal>,
+}
+
+impl Panel {
+    /// Rectangle with top left corner at `at`
+    pub fn rect(role: Role, at: Point, square: Square, borders: Borders) -> Self {
+        Self {
+            name: None,
+            role,
+            vertices: vec![
+                at,
+                at.shift_x(square.w),
+                at.shift_xy(square.w, square.h),
+                at.shift_y(square.h),
+            ],
+            edges: vec![borders.top, borders.right, borders.bottom, borders.left],
+            shapes: vec![EdgeShape::Straight; 4],
+            hinge: None,
+            thickness: None,
+        }
+    }
+
+    /// Rectangle with corners rounded by `radius`, also returns edge index of every side.
+    /// Corner arcs are cut when any of the adjacent sides is cut.
+    pub fn rounded_rect(
+        role: Role,
+        at: Point,
+        square: Square,
+        borders: Borders,
+        radius: Decimal,
+    ) -> (Self, [usize; 4]) {
+        let r = radius
+            .min(square.w / Decimal::TWO)
+            .min(square.h / Decimal::TWO);
+        let (w, h) = (square.w, square.h);
+        // Side start, side end and corner center going clockwise from the top side
+        let sides = [
+            (at.shift_x(r), at.shift_x(w - r), at.shift_xy(w - r, r)),
+            (
+                at.shift_xy(w, r),
+                at.shift_xy(w, h - r),
+                at.shift_xy(w - r, h - r),
+            ),
+            (
+                at.shift_xy(w - r, h),
+                at.shift_xy(r, h),
+                at.shift_xy(r, h - r),
+            ),
+            (at.shift_y(h - r), at.shift_y(r), at.shift_xy(r, r)),
+        ];
+        let types = [borders.top, borders.right, borders.bottom, borders.left];
+
+        let mut panel = Self {
+            name: None,
+            role,
+            vertices: Vec::new(),
+            edges: Vec::new(),
+            shapes: Vec::new(),
+            hinge: None,
+            thickness: None,
+        };
+        let mut index = [0; 4];
+
+        for (i, (from, to, center)) in sides.into_iter().enumerate() {
+            // Side of zero length is left out, the corners meet
+            if from.x != to.x || from.y != to.y {
+                index[i] = panel.vertices.len();
+                panel.vertices.push(from);
+                panel.edges.push(types[i]);
+                panel.shapes.push(EdgeShape::Straight);
+            } else {
+                index[i] = panel.vertices.len();
+            }
+
+            if r > Decimal::ZERO {
+                let next = types[(i + 1) % 4];
+                let corner = match (types[i], next) {
+                    (CutType::Cut, _) | (_, CutType::Cut) => CutType::Cut,
+                    (CutType::Nope, _) | (_, CutType::Nope) => CutType::Nope,
+                    _ => CutType::Bend,
+                };
+                panel.vertices.push(to);
+                panel.edges.push(corner);
+                panel.shapes.push(EdgeShape::Arc {
+                    center,
+                    clockwise: true,
+                });
+            }
+        }
+
+        (panel, index)
+    }
+
+    /// Round hole made of four quarter arcs, so the vertices give the bounding box
+    pub fn circle(role: Role, center: Point, radius: Decimal) -> Self {
+        let vertices = vec![
+            center.shift_ny(radius),
+            center.shift_x(radius),
+            center.shift_y(radius),
+            center.shift_nx(radius),
+        ];
+        Self {
+            name: None,
+            role,
+            vertices,
+            edges: vec![CutType::Cut; 4],
+            shapes: vec![
+                EdgeShape::Arc {
+                    center,
+                    clockwise: true,
+                };
+                4
+            ],
+            hinge: None,
+            thickness: None,
+        }
+    }
+
+    /// Cut a half circle into the middle of the straight `edge`, it goes inside the panel.
+    /// Both ends of the edge keep its cut type, the hinge edge index is kept pointing to the same edge.
+    pub fn notch(&mut self, edge: usize, radius: Decimal) {
+        let (from, to) = self.edge(edge);
+        let len = Decimal::from_f64(self.edge_len(edge)).unwrap_or_default();
+        assert!(
+            radius * Decimal::TWO < len,
+            "Notch does not fit into the edge"
+        );
+
+        let (ux, uy) = ((to.x - from.x) / len, (to.y - from.y) / len);
+        let mid = Point::new(
+            (from.x + to.x) / Decimal::TWO,
+            (from.y + to.y) / Decimal::TWO,
+        );
+        let start = mid.shift_xy(-ux * radius, -uy * radius);
+        let end = mid.shift_xy(ux * radius, uy * radius);
+
+        let tp = self.edges[edge];
+        self.vertices.insert(edge + 1, start);
+        self.vertices.insert(edge + 2, end);
+        self.edges.insert(edge + 1, tp);
+        self.edges.insert(edge + 1, tp);
+        // Counterclockwise arc of the clockwise outline bulges inside
+        self.shapes.insert(edge + 1, EdgeShape::Straight);
+        self.shapes.insert(
+            edge + 1,
+            EdgeShape::Arc {
+                center: mid,
+                clockwise: false,
+            },
+        );
+
+        if let Some(hinge) = self.hinge.as_mut() {
+            assert_ne!(hinge.edge, edge, "Notch on the hinge edge");
+            if hinge.edge > edge {
+                hinge.edge += 2;
+            }
+        }
+    }
+
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.as_deref() == Some(name)
+    }
+
+    /// Ends of the edge in clockwise order
+    pub fn edge(&self, index: usize) -> (Point, Point) {
+        (
+            self.vertices[index],
+            self.vertices[(index + 1) % self.vertices.len()],
+        )
+    }
+
+    pub fn edge_len(&self, index: usize) -> f64 {
+        let (from, to) = self.edge(index);
+        let ((x1, y1), (x2, y2)) = (from.to_f64(), to.to_f64());
+        (x2 - x1).hypot(y2 - y1)
+    }
+
+    /// Cut and bend segments of the outline
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        (0..self.edges.len()).filter_map(|i| {
+            let (from, to) = self.edge(i);
+            let tp = &self.edges[i];
+            match self.shapes[i] {
+                EdgeShape::Straight => draw_line(from, to, tp).map(Segment::Line),
+                EdgeShape::Arc { center, clockwise } => {
+                    draw_arc(from, to, center, clockwise, tp).map(Segment::Arc)
+                }
+            }
+        })
+    }
+
+    /// Top left and bottom right corners of the bounding box
+    pub fn bounds(&self) -> (Point, Point) {
+        let mut min = self.vertices[0];
+        let mut max = self.vertices[0];
+        for v in self.vertices.iter() {
+            min.x = min.x.min(v.x);
+            min.y = min.y.min(v.y);
+            max.update_max(*v);
+        }
+        (min, max)
+    }
+
+    /// Area inside the outline including arcs
+    pub fn area(&self) -> f64 {
+        let twice: f64 = (0..self.vertices.len())
+            .map(|i| {
+                let (from, to) = self.edge(i);
+                let ((x1, y1), (x2, y2)) = (from.to_f64(), to.to_f64());
+                match self.shapes[i] {
+                    EdgeShape::Straight => x1 * y2 - x2 * y1,
+                    EdgeShape::Arc { center, clockwise } => {
+                        let arc = Arc {
+                            from,
+                            to,
+                            center,
+                            clockwise,
+                            tp: CutType::Cut,
+                        };
+                        let (cx, cy) = center.to_f64();
+                        cx * (y2 - y1) - cy * (x2 - x1) + arc.radius().powi(2) * arc.angles().1
+                    }
+                }
+            })
+            .sum();
+        twice.abs() / 2.0
+    }
+}
+
+/// Glue flap attached to the target panel in the folded box
+#[derive(Debug, Clone)]
+pub struct GluePair {
+    pub flap: String,
+    pub target: String,
+}
+
+/// Structure of the box: board thickness, panels connected with hinges and glued together
+#[derive(Debug, Clone, Default)]
+pub struct Net {
+    pub thickness: Decimal,
+    pub panels: Vec<Panel>,
+    pub glue: Vec<GluePair>,
+}
+
+impl Net {
+    pub fn new(thickness: Decimal) -> Self {
+        Self {
+            thickness,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, panel: Panel) {
+        self.panels.push(panel);
+    }
+
+    /// Declare that the flap is glued to the target
+    pub fn glue(&mut self, flap: &str, target: &str) {
+        self.glue.push(GluePair {
+            flap: flap.to_string(),
+            target: target.to_string(),
+        });
+    }
+
+    pub fn append(&mut self, other: Net) {
+        if self.thickness < other.thickness {
+            self.thickness = other.thickness;
+        }
+        self.panels.extend(other.panels);
+        self.glue.extend(other.glue);
+    }
+
+    pub fn panel(&self, name: &str) -> Option<&Panel> {
+        self.panels.iter().find(|p| p.is_named(name))
+    }
+
+    /// Board thickness of the panel
+    pub fn thickness_of(&self, panel: &Panel) -> Decimal {
+        panel.thickness.unwrap_or(self.thickness)
+    }
+
+    /// Write the net thickness into the panels, so they keep it on the sheet with other boards
+    pub fn keep_thickness(&mut self) {
+        for p in self.panels.iter_mut() {
+            p.thickness.get_or_insert(self.thickness);
+        }
+    }
+
+    /// Prepend panel names, so nets of different parts could be put on one sheet
+    pub fn prefix(&mut self, prefix: &str) {
+        for p in self.panels.iter_mut() {
+            if let Some(name) = p.name.as_mut() {
+                name.insert_str(0, prefix);
+            }
+            if let Some(hinge) = p.hinge.as_mut() {
+                hinge.parent.insert_str(0, prefix);
+            }
+        }
+        for g in self.glue.iter_mut() {
+            g.flap.insert_str(0, prefix);
+            g.target.insert_str(0, prefix);
+        }
+    }
+
+    /// Cut and bend segments of all panels
+    pub fn segments(&self) -> Vec<Segment> {
+        self.panels.iter().flat_map(|p| p.segments()).collect()
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{bail, Result};
+use clap::ArgMatches;
+use rust_decimal::Decimal;
+
+use super::args::{NotchOn, ThumbNotch};
+use crate::common::{DrawResult, Net, Panel, Point, Role};
+
+/// Where the thumb notch is cut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotchPlace {
+    /// Lid skirt, it is doubled so the notch is a round hole on the fold
+    Lid,
+    /// Upper edge of the box front
+    Front,
+}
+
+/// Semicircular thumb notch to open a flush lid
+#[derive(Debug, Clone, Copy)]
+pub struct Notch {
+    pub radius: Decimal,
+    pub place: NotchPlace,
+}
+
+impl Notch {
+    /// None when `--notch` is not given, the place is taken from `--notch-on`
+    pub fn from_matches(m: &ArgMatches) -> Result<Option<Self>> {
+        Self::placed_at(m, NotchOn::extract(m))
+    }
+
+    /// For models without `--notch-on`, the notch always goes to the `place`
+    pub fn placed_at(m: &ArgMatches, place: NotchPlace) -> Result<Option<Self>> {
+        let Some(radius) = ThumbNotch::extract(m) else {
+            return Ok(None);
+        };
+        if radius <= Decimal::ZERO {
+            bail!("Радиус выемки должен быть больше 0: {radius}");
+        }
+
+        Ok(Some(Self { radius, place }))
+    }
+
+    /// The notch when it is asked for the `place` and fits into the edge of `along` length
+    /// on the panel of `depth`
+    pub fn placed(&self, place: NotchPlace, along: Decimal, depth: Decimal) -> Option<Self> {
+        if self.place != place {
+            return None;
+        }
+        if self.radius * Decimal::TWO >= along || self.radius >= depth {
+            log::warn!("Выемка под палец не поместилась, радиус слишком большой");
+            return None;
+        }
+        Some(*self)
+    }
+
+    /// Round hole on the fold line of a doubled wall, each layer gets a half of it
+    pub fn draw_hole(&self, center: Point) -> DrawResult {
+        let panel = Panel::circle(Role::Hole, center, self.radius);
+        let max = panel.bounds().1;
+
+        let mut net = Net::default();
+        net.add(panel);
+        DrawResult::new(net, max)
+    }
+}
@@ -0,0 +1,339 @@
+//! Checks that cut lines form closed outlines, otherwise the part won't separate from the sheet.
+use std::fmt;
+
+use super::{CutType, Segment};
+
+/// Point on the sheet, approximate math is enough here
+type Xy = (f64, f64);
+
+/// Cut ends closer than this are treated as connected (mm)
+pub const TOLERANCE: f64 = 0.01;
+
+/// Cut ends closer than this but not connected are reported as a gap (mm)
+pub const MAX_GAP: f64 = 2.0;
+
+#[derive(Debug, Clone)]
+pub enum OutlineIssue {
+    /// Cut ends without meeting another cut
+    Dangling { at: Xy },
+    /// Two cut ends almost meet
+    Gap { from: Xy, to: Xy, size: f64 },
+    /// Connected cuts which do not enclose anything, top left and bottom right corners
+    Open { min: Xy, max: Xy },
+}
+
+impl fmt::Display for OutlineIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutlineIssue::Dangling { at } => {
+                write!(f, "Рез обрывается в ({:.2}, {:.2})", at.0, at.1)
+            }
+            OutlineIssue::Gap { from, to, size } => write!(
+                f,
+                "Разрыв {size:.2}мм между ({:.2}, {:.2}) и ({:.2}, {:.2})",
+                from.0, from.1, to.0, to.1
+            ),
+            OutlineIssue::Open { min, max } => write!(
+                f,
+                "Резы в области ({:.2}, {:.2}) - ({:.2}, {:.2}) не замыкаются, деталь не отделится",
+                min.0, min.1, max.0, max.1
+            ),
+        }
+    }
+}
+
+fn dist(a: Xy, b: Xy) -> f64 {
+    (b.0 - a.0).hypot(b.1 - a.1)
+}
+
+/// Distance from the point to the segment
+fn dist_to_segment(p: Xy, (a, b): (Xy, Xy)) -> f64 {
+    let len2 = (b.0 - a.0).powi(2) + (b.1 - a.1).powi(2);
+    if len2 == 0.0 {
+        return dist(p, a);
+    }
+    let t = (((p.0 - a.0) * (b.0 - a.0) + (p.1 - a.1) * (b.1 - a.1)) / len2).clamp(0.0, 1.0);
+    dist(p, (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t))
+}
+
+/// Straight pieces of the segments of the type
+fn pieces(segments: &[Segment], tp: CutType) -> Vec<(Xy, Xy)> {
+    segments
+        .iter()
+        .filter(|s| s.tp() == tp)
+        .flat_map(|s| {
+            let points = s.flatten();
+            points.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>()
+        })
+        .filter(|(a, b)| dist(*a, *b) > TOLERANCE)
+        .collect()
+}
+
+/// Graph of cut lines: merged end points and segments between them
+struct CutGraph {
+    nodes: Vec<Xy>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl CutGraph {
+    fn new(segments: &[Segment]) -> Self {
+        // Curves are checked as straight pieces
+        let cuts = pieces(segments, CutType::Cut);
+
+        let mut graph = Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        let ends: Vec<Xy> = cuts.iter().flat_map(|(a, b)| [*a, *b]).collect();
+
+        for (a, b) in cuts.iter() {
+            // Split the cut where other cuts end on it
+            let len = dist(*a, *b);
+            let (ux, uy) = ((b.0 - a.0) / len, (b.1 - a.1) / len);
+            let mut stops: Vec<f64> = ends
+                .iter()
+                .filter_map(|p| {
+                    let (dx, dy) = (p.0 - a.0, p.1 - a.1);
+                    let along = dx * ux + dy * uy;
+                    let across = (dx * uy - dy * ux).abs();
+                    (across <= TOLERANCE && along > TOLERANCE && along < len - TOLERANCE)
+                        .then_some(along)
+                })
+                .collect();
+            stops.push(0.0);
+            stops.push(len);
+            stops.sort_by(f64::total_cmp);
+
+            for w in stops.windows(2) {
+                let from = graph.node((a.0 + ux * w[0], a.1 + uy * w[0]));
+                let to = graph.node((a.0 + ux * w[1], a.1 + uy * w[1]));
+                let edge = (from.min(to), from.max(to));
+                if from != to && !graph.edges.contains(&edge) {
+                    graph.edges.push(edge);
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn node(&mut self, p: Xy) -> usize {
+        match self.nodes.iter().position(|n| dist(*n, p) <= TOLERANCE) {
+            Some(i) => i,
+            None => {
+                self.nodes.push(p);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn degrees(&self) -> Vec<usize> {
+        let mut deg = vec![0; self.nodes.len()];
+        for (a, b) in self.edges.iter() {
+            deg[*a] += 1;
+            deg[*b] += 1;
+        }
+        deg
+    }
+
+    /// Component index of every node
+    fn components(&self) -> Vec<usize> {
+        let mut parent: Vec<usize> = (0..self.nodes.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for (a, b) in self.edges.iter() {
+            let (ra, rb) = (root(&mut parent, *a), root(&mut parent, *b));
+            parent[ra] = rb;
+        }
+        (0..self.nodes.len())
+            .map(|i| root(&mut parent, i))
+            .collect()
+    }
+}
+
+/// Find cut ends which go nowhere, small gaps and cut groups without closed outline.
+/// Cuts ending on a bend line outline a flap which stays attached on purpose.
+pub fn check_outline(segments: &[Segment]) -> Vec<OutlineIssue> {
+    let graph = CutGraph::new(segments);
+    let bends = pieces(segments, CutType::Bend);
+    let deg = graph.degrees();
+    let mut issues = Vec::new();
+
+    let on_bend = |i: usize| {
+        bends
+            .iter()
+            .any(|b| dist_to_segment(graph.nodes[i], *b) <= TOLERANCE)
+    };
+    let dangling: Vec<usize> = (0..graph.nodes.len())
+        .filter(|i| deg[*i] == 1 && !on_bend(*i))
+        .collect();
+    let mut in_gap = vec![false; graph.nodes.len()];
+
+    for (k, a) in dangling.iter().enumerate() {
+        for b in dangling[k + 1..].iter() {
+            let size = dist(graph.nodes[*a], graph.nodes[*b]);
+            if size <= MAX_GAP {
+                in_gap[*a] = true;
+                in_gap[*b] = true;
+                issues.push(OutlineIssue::Gap {
+                    from: graph.nodes[*a],
+                    to: graph.nodes[*b],
+                    size,
+                });
+            }
+        }
+    }
+
+    for a in dangling.iter().filter(|a| !in_gap[**a]) {
+        issues.push(OutlineIssue::Dangling {
+            at: graph.nodes[*a],
+        });
+    }
+
+    // Component without cycles can't enclose a part
+    let comp = graph.components();
+    let mut roots: Vec<usize> = comp.clone();
+    roots.sort();
+    roots.dedup();
+
+    for r in roots {
+        let nodes: Vec<Xy> = (0..graph.nodes.len())
+            .filter(|i| comp[*i] == r)
+            .map(|i| graph.nodes[i])
+            .collect();
+        let edges = graph.edges.iter().filter(|(a, _)| comp[*a] == r).count();
+        let flap = (0..graph.nodes.len())
+            .filter(|i| comp[*i] == r && deg[*i] == 1)
+            .all(on_bend);
+
+        if edges < nodes.len() && !flap {
+            let mut min = nodes[0];
+            let mut max = nodes[0];
+            for p in nodes.iter() {
+                min = (min.0.min(p.0), min.1.min(p.1));
+                max = (max.0.max(p.0), max.1.max(p.1));
+            }
+            issues.push(OutlineIssue::Open { min, max });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::args::ModelParams;
+    use crate::common::{draw_circle, Line, Point};
+
+    fn cut(x1: f64, y1: f64, x2: f64, y2: f64) -> Segment {
+        Segment::Line(Line {
+            from: Point::from_f64(x1, y1),
+            to: Point::from_f64(x2, y2),
+            tp: CutType::Cut,
+        })
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Segment> {
+        vec![
+            cut(x, y, x + size, y),
+            cut(x + size, y, x + size, y + size),
+            cut(x + size, y + size, x, y + size),
+            cut(x, y + size, x, y),
+        ]
+    }
+
+    #[test]
+    fn closed_outlines() {
+        assert!(check_outline(&square(0.0, 0.0, 10.0)).is_empty());
+
+        // Slot cut into the square side ends on another cut
+        let mut lines = square(0.0, 0.0, 10.0);
+        lines.push(cut(5.0, 0.0, 5.0, 4.0));
+        lines.push(cut(5.0, 4.0, 6.0, 4.0));
+        lines.push(cut(6.0, 4.0, 6.0, 0.0));
+        assert!(check_outline(&lines).is_empty());
+
+        // Bends do not count
+        let mut lines = square(0.0, 0.0, 10.0);
+        lines.push(Segment::Line(Line {
+            from: Point::from_f64(0.0, 5.0),
+            to: Point::from_f64(30.0, 5.0),
+            tp: CutType::Bend,
+        }));
+        assert!(check_outline(&lines).is_empty());
+
+        // Flap cut ends on the bend line
+        let mut lines = square(0.0, 0.0, 10.0);
+        lines.push(cut(2.0, 2.0, 2.0, 6.0));
+        lines.push(cut(2.0, 6.0, 6.0, 6.0));
+        lines.push(cut(6.0, 6.0, 6.0, 2.0));
+        lines.push(Segment::Line(Line {
+            from: Point::from_f64(2.0, 2.0),
+            to: Point::from_f64(6.0, 2.0),
+            tp: CutType::Bend,
+        }));
+        assert!(check_outline(&lines).is_empty());
+
+        // Circle closes on itself
+        let circle = draw_circle(Point::from_f64(5.0, 5.0), 3.into(), &CutType::Cut).unwrap();
+        assert!(check_outline(&[Segment::Arc(circle)]).is_empty());
+    }
+
+    #[test]
+    fn broken_outlines() {
+        let mut lines = square(0.0, 0.0, 10.0);
+        lines.pop();
+        let issues = check_outline(&lines);
+        assert_eq!(issues.len(), 3, "{issues:?}");
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|i| matches!(i, OutlineIssue::Dangling { .. }))
+                .count(),
+            2
+        );
+        assert!(matches!(issues[2], OutlineIssue::Open { .. }));
+
+        let mut lines = square(0.0, 0.0, 10.0);
+        lines[0] = cut(0.0, 0.0, 9.5, 0.0);
+        let issues = check_outline(&lines);
+        assert!(
+            matches!(issues[..], [OutlineIssue::Gap { size, .. }, OutlineIssue::Open { .. }] if (size - 0.5).abs() < 1e-9),
+            "{issues:?}"
+        );
+    }
+
+    #[test]
+    fn models_separate_from_sheet() {
+        let cases: &[(&str, &[(&str, &str)])] = &[
+            ("box-cuboid", &[("l", "300"), ("w", "200"), ("h", "100")]),
+            (
+                "box-cuboid",
+                &[("l", "120"), ("w", "80"), ("h", "50"), ("t", "3")],
+            ),
+            ("lid", &[("l", "300"), ("w", "200"), ("h", "50")]),
+            (
+                "lid",
+                &[("l", "250"), ("w", "150"), ("h", "40"), ("fat", "true")],
+            ),
+            ("vinyl", &[("w", "100")]),
+            ("vinyl", &[("w", "60"), ("t", "1.5")]),
+        ];
+
+        for (model, values) in cases {
+            let mut params = ModelParams::new(model);
+            for (name, value) in values.iter() {
+                params.set(name, value);
+            }
+            let issues = crate::draw_params(&params).unwrap().outline_issues();
+            assert!(issues.is_empty(), "{model} {values:?}: {issues:?}");
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::common::{CutType, DrawResult, EdgeShape, Hinge, Net, Panel, Point, Role, Side, Square};
+
+/// Panel of any shape, vertices go clockwise on the sheet
+#[derive(Debug, Clone)]
+pub struct PolygonElement {
+    /// Relative to the top left corner of the bounding box
+    vertices: Vec<Point>,
+    /// Cut type of the edge from the vertex with the same index to the next one
+    edges: Vec<CutType>,
+    /// Shape of the edge with the same index, arc centers are relative like vertices
+    shapes: Vec<EdgeShape>,
+    /// Bounding box, used for the origin alignment
+    pub square: Square,
+    role: Role,
+    name: Option<&'static str>,
+    hinge: Option<(&'static str, usize, f64)>,
+}
+
+impl PolygonElement {
+    /// Polygon with one cut type per edge, it is moved to have top left corner of the bounding box at zero.
+    /// None for less than 3 vertices, e.g. the sizes from the command line leave nothing to draw.
+    pub fn new(vertices: &[Point], edges: &[CutType]) -> Option<Self> {
+        assert_eq!(vertices.len(), edges.len(), "One cut type per edge");
+        if vertices.len() < 3 {
+            return None;
+        }
+
+        let mut min = vertices[0];
+        for v in vertices.iter() {
+            min.x = min.x.min(v.x);
+            min.y = min.y.min(v.y);
+        }
+        let vertices: Vec<Point> = vertices
+            .iter()
+            .map(|v| Point::new(v.x - min.x, v.y - min.y))
+            .collect();
+
+        let mut max = Point::zero();
+        for v in vertices.iter() {
+            max.update_max(*v);
+        }
+
+        Some(Self {
+            vertices,
+            edges: edges.to_vec(),
+            shapes: vec![EdgeShape::Straight; edges.len()],
+            square: Square::new(max.x, max.y),
+            role: Role::Mark,
+            name: None,
+            hinge: None,
+        })
+    }
+
+    /// Edges which are not straight, coordinates are relative to the top left corner of the bounding box
+    pub fn with_shapes(&self, shapes: &[EdgeShape]) -> Self {
+        assert_eq!(self.edges.len(), shapes.len(), "One shape per edge");
+        let mut new = self.clone();
+        new.shapes = shapes.to_vec();
+        new
+    }
+
+    pub fn role(&self, role: Role) -> Self {
+        let mut new = self.clone();
+        new.role = role;
+        new
+    }
+
+    /// Name of the panel in the box net
+    pub fn named(&self, name: &'static str) -> Self {
+        let mut new = self.clone();
+        new.name = Some(name);
+        new
+    }
+
+    /// Attach `edge` of the element to the `parent` panel folded by `angle` degrees
+    pub fn hinged(&self, parent: &'static str, edge: usize, angle: f64) -> Self {
+        let mut new = self.clone();
+        new.hinge = Some((parent, edge, angle));
+        new
+    }
+
+    /// Attach the straight edge lying on the `side` of the bounding box
+    pub fn hinged_side(&self, parent: &'static str, side: Side, angle: f64) -> Self {
+        let Square { w, h } = self.square;
+        let on_side = |p: &Point| match side {
+            Side::Top => p.y.is_zero(),
+            Side::Right => p.x == w,
+            Side::Bottom => p.y == h,
+            Side::Left => p.x.is_zero(),
+        };
+
+        let n = self.vertices.len();
+        let edge = (0..n)
+            .find(|i| {
+                matches!(self.shapes[*i], EdgeShape::Straight)
+                    && on_side(&self.vertices[*i])
+                    && on_side(&self.vertices[(i + 1) % n])
+            })
+            .expect("No edge on the side of the polygon");
+        self.hinged(parent, edge, angle)
+    }
+
+    /// Flip left to right
+    pub fn mirror_vertical(&self) -> Self {
+        let w = self.square.w;
+        self.mirrored(|p| Point::new(w - p.x, p.y))
+    }
+
+    /// Flip top to bottom
+    pub fn mirror_horisontal(&self) -> Self {
+        let h = self.square.h;
+        self.mirrored(|p| Point::new(p.x, h - p.y))
+    }
+
+    /// Flipped outline goes counterclockwise, so the order is reversed to keep it clockwise.
+    /// Edge `i` of the result is the edge `n - 2 - i` of the source.
+    fn mirrored(&self, flip: impl Fn(Point) -> Point) -> Self {
+        let n = self.vertices.len();
+        let source_edge = |i: usize| (2 * n - 2 - i) % n;
+
+        let mut mirrored = self.clone();
+        mirrored.vertices = self.vertices.iter().rev().map(|v| flip(*v)).collect();
+        mirrored.edges = (0..n).map(|i| self.edges[source_edge(i)]).collect();
+        // Arc direction flips twice: by mirroring and by the reversed order
+        mirrored.shapes = (0..n)
+            .map(|i| match self.shapes[source_edge(i)] {
+                EdgeShape::Arc { center, clockwise } => EdgeShape::Arc {
+                    center: flip(center),
+                    clockwise,
+                },
+                other => other,
+            })
+            .collect();
+        // The same formula maps edges back
+        mirrored.hinge = self
+            .hinge
+            .map(|(parent, edge, angle)| (parent, source_edge(edge), angle));
+        mirrored
+    }
+
+    pub fn draw(&self, offset: Point) -> DrawResult {
+        let from = offset.align_top_left(self.square);
+
+        let panel = Panel {
+            name: self.name.map(String::from),
+            role: self.role,
+            vertices: self.vertices.iter().map(|v| from.shift_with(*v)).collect(),
+            edges: self.edges.clone(),
+            shapes: self
+                .shapes
+                .iter()
+                .map(|s| match s {
+                    EdgeShape::Arc { center, clockwise } => EdgeShape::Arc {
+                        center: from.shift_with(*center),
+                        clockwise: *clockwise,
+                    },
+                    other => *other,
+                })
+                .collect(),
+            hinge: self.hinge.map(|(parent, edge, angle)| Hinge {
+                parent: parent.to_string(),
+                edge,
+                angle,
+            }),
+            thickness: None,
+        };
+
+        let max = from.shift_xy(self.square.w, self.square.h);
+
+        let mut net = Net::default();
+        net.add(panel);
+        DrawResult::new(net, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::common::{dec, Borders, FlapStyle, Origin};
+
+    fn pt(x: u32, y: u32) -> Point {
+        Point::new(dec(x, 0), dec(y, 0))
+    }
+
+    /// Trapezoid narrowing to the top with the bend at the bottom
+    fn flap() -> PolygonElement {
+        PolygonElement::new(
+            &[pt(12, 5), pt(22, 5), pt(30, 15), pt(10, 15)],
+            &[CutType::Cut, CutType::Nope, CutType::Bend, CutType::Cut],
+        )
+        .unwrap()
+        .hinged("wall", 2, 90.0)
+    }
+
+    fn coords(res: &DrawResult) -> Vec<(Decimal, Decimal)> {
+        res.net.panels[0]
+            .vertices
+            .iter()
+            .map(|v| (v.x, v.y))
+            .collect()
+    }
+
+    #[test]
+    fn aligned_by_origin() {
+        let res = flap().draw(pt(100, 100).origin(Origin::BottomRight));
+        assert_eq!(
+            coords(&res),
+            [pt(82, 90), pt(92, 90), pt(100, 100), pt(80, 100)].map(|p| (p.x, p.y))
+        );
+        assert_eq!((res.max.x, res.max.y), (dec(100, 0), dec(100, 0)));
+    }
+
+    #[test]
+    fn mirroring_keeps_edges() {
+        let res = flap().mirror_vertical().draw(Point::zero());
+        let panel = &res.net.panels[0];
+        assert_eq!(
+            coords(&res),
+            [pt(20, 10), pt(0, 10), pt(8, 0), pt(18, 0)].map(|p| (p.x, p.y))
+        );
+        assert!(matches!(
+            panel.edges[..],
+            [CutType::Bend, CutType::Nope, CutType::Cut, CutType::Cut]
+        ));
+        // Still the bottom edge
+        let hinge = panel.hinge.as_ref().unwrap().edge;
+        assert_eq!(panel.edge(hinge).0.y, dec(10, 0));
+        assert_eq!(panel.edge(hinge).1.y, dec(10, 0));
+
+        let res = flap().mirror_horisontal().draw(Point::zero());
+        let panel = &res.net.panels[0];
+        let hinge = panel.hinge.as_ref().unwrap().edge;
+        assert!(matches!(panel.edges[hinge], CutType::Bend));
+        assert_eq!(panel.edge(hinge).0.y, Decimal::ZERO);
+        assert_eq!(panel.edge(hinge).1.y, Decimal::ZERO);
+    }
+
+    #[test]
+    fn degenerate_flap_is_dropped() {
+        assert!(PolygonElement::new(&[pt(0, 0), pt(10, 0)], &[CutType::Cut; 2]).is_none());
+
+        let borders = Borders::new(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut);
+        for style in [FlapStyle::Square, FlapStyle::Chamfer, FlapStyle::Rounded] {
+            let flap = style.flap(
+                Square::new(Decimal::ZERO, dec(20, 0)),
+                borders,
+                Side::Bottom,
+            );
+            assert!(flap.is_none());
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Material and laser time needed for the drawing.
+use std::fmt;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use super::outline::TOLERANCE;
+use super::{CutType, DrawResult, Segment};
+use crate::output::sheet_size;
+
+/// Point on the sheet, approximate math is enough here
+type Xy = (f64, f64);
+
+/// Lines with unit directions closer than this are parallel
+const DIRECTION_TOLERANCE: f64 = 1e-6;
+
+/// Lines on one straight: direction, offset from the origin and spans along the direction
+type Straight = (Xy, f64, Vec<(f64, f64)>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+/// Laser speeds and prices for the estimate
+#[derive(Debug, Clone, Copy)]
+pub struct Machine {
+    /// Cut through speed (mm/s)
+    pub cut_speed: Decimal,
+    /// Bend line speed (mm/s)
+    pub bend_speed: Decimal,
+    /// Price of the board per square meter
+    pub price_m2: Option<Decimal>,
+    /// Price of the laser per minute
+    pub price_minute: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Cost {
+    pub material: f64,
+    pub machine: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Report {
+    /// Sheet size with margins (mm)
+    pub sheet: Xy,
+    /// Sheet area (m²)
+    pub area: f64,
+    /// Total length of cuts (mm), overlapping cuts of neighbour panels are counted once
+    pub cut_length: f64,
+    /// Total length of bend lines (mm)
+    pub bend_length: f64,
+    /// Laser time without idle moves (s)
+    pub time: f64,
+    pub cost: Option<Cost>,
+}
+
+impl Report {
+    pub fn new(drawing: &DrawResult, machine: &Machine) -> Self {
+        let max = sheet_size(drawing).to_f64();
+        let area = max.0 * max.1 / 1_000_000.0;
+
+        let segments = drawing.segments();
+        let cut_length = total_length(&segments, CutType::Cut);
+        let bend_length = total_length(&segments, CutType::Bend);
+        let time =
+            cut_length / to_f64(machine.cut_speed) + bend_length / to_f64(machine.bend_speed);
+
+        let cost = (machine.price_m2.is_some() || machine.price_minute.is_some()).then(|| Cost {
+            material: area * machine.price_m2.map(to_f64).unwrap_or_default(),
+            machine: time / 60.0 * machine.price_minute.map(to_f64).unwrap_or_default(),
+        });
+
+        Self {
+            sheet: max,
+            area,
+            cut_length,
+            bend_length,
+            time,
+            cost,
+        }
+    }
+
+    pub fn to_json(self) -> serde_json::Value {
+        let mut json = json!({
+            "sheet": { "width": round(self.sheet.0), "height": round(self.sheet.1) },
+            "area_m2": round(self.area),
+            "cut_length_mm": round(self.cut_length),
+            "bend_length_mm": round(self.bend_length),
+            "time_s": round(self.time),
+        });
+        if let Some(cost) = self.cost {
+            json["cost"] = json!({
+                "material": round(cost.material),
+                "machine": round(cost.machine),
+                "total": round(cost.material + cost.machine),
+            });
+        }
+        json
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Отчет:\n - Лист: {:.1}x{:.1}мм, {:.3}м²\n - Резы: {:.0}мм\n - Сгибы: {:.0}мм\n - Время резки: {}",
+            self.sheet.0,
+            self.sheet.1,
+            self.area,
+            self.cut_length,
+            self.bend_length,
+            minutes(self.time)
+        )?;
+        if let Some(cost) = self.cost {
+            write!(
+                f,
+                "\n - Стоимость: {:.2} (материал {:.2}, работа {:.2})",
+                cost.material + cost.machine,
+                cost.material,
+                cost.machine
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn to_f64(v: Decimal) -> f64 {
+    v.to_f64().unwrap_or_default()
+}
+
+/// Up to 3 decimal places for JSON
+fn round(v: f64) -> f64 {
+    (v * 1000.0).round() / 1000.0
+}
+
+fn minutes(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!("{}мин {}с", seconds / 60, seconds % 60)
+}
+
+/// Length of the segments of the type, straight lines on top of each other are counted once
+fn total_length(segments: &[Segment], tp: CutType) -> f64 {
+    let mut lines: Vec<(Xy, Xy)> = Vec::new();
+    let mut curves = 0.0;
+    for s in segments.iter().filter(|s| s.tp() == tp) {
+        match s {
+            Segment::Line(l) => lines.push((l.from.to_f64(), l.to.to_f64())),
+            Segment::Arc(a) => curves += a.radius() * a.angles().1.abs(),
+            Segment::Cubic(_) => {
+                curves += s
+                    .flatten()
+                    .windows(2)
+                    .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+                    .sum::<f64>()
+            }
+        }
+    }
+
+    curves + straight_length(&lines)
+}
+
+/// Length of the union of the lines
+fn straight_length(lines: &[(Xy, Xy)]) -> f64 {
+    let mut groups: Vec<Straight> = Vec::new();
+    for (a, b) in lines {
+        let len = (b.0 - a.0).hypot(b.1 - a.1);
+        if len <= TOLERANCE {
+            continue;
+        }
+        let mut dir = ((b.0 - a.0) / len, (b.1 - a.1) / len);
+        if dir.0 < -TOLERANCE || (dir.0.abs() <= TOLERANCE && dir.1 < 0.0) {
+            dir = (-dir.0, -dir.1);
+        }
+        let offset = a.0 * dir.1 - a.1 * dir.0;
+        let (from, to) = (a.0 * dir.0 + a.1 * dir.1, b.0 * dir.0 + b.1 * dir.1);
+        let span = (from.min(to), from.max(to));
+
+        let same = |(d, o, _): &&mut Straight| {
+            (d.0 - dir.0).abs() <= DIRECTION_TOLERANCE
+                && (d.1 - dir.1).abs() <= DIRECTION_TOLERANCE
+                && (o - offset).abs() <= TOLERANCE
+        };
+        match groups.iter_mut().find(same) {
+            Some(g) => g.2.push(span),
+            None => groups.push((dir, offset, vec![span])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, _, mut spans)| {
+            spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut total = 0.0;
+            let mut current: Option<(f64, f64)> = None;
+            for (from, to) in spans {
+                current = match current {
+                    Some((f, t)) if from <= t + TOLERANCE => Some((f, t.max(to))),
+                    Some((f, t)) => {
+                        total += t - f;
+                        Some((from, to))
+                    }
+                    None => Some((from, to)),
+                };
+            }
+            total + current.map(|(f, t)| t - f).unwrap_or_default()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{dec, Point, SquareElement};
+
+    fn machine() -> Machine {
+        Machine {
+            cut_speed: dec(10, 0),
+            bend_speed: dec(20, 0),
+            price_m2: None,
+            price_minute: None,
+        }
+    }
+
+    #[test]
+    fn shared_cut_is_counted_once() {
+        let square = SquareElement::cut(dec(10, 0), dec(10, 0));
+        let mut drawing = square.draw(Point::zero());
+        drawing.append(square.draw(Point::new(dec(10, 0), Decimal::ZERO)));
+        drawing.append(square.draw(Point::new(dec(5, 0), Decimal::ZERO)));
+
+        let report = Report::new(&drawing, &machine());
+        assert!((report.cut_length - 90.0).abs() < 1e-6, "{report:?}");
+        assert_eq!(report.bend_length, 0.0);
+        assert!((report.time - 9.0).abs() < 1e-6);
+        assert!(report.cost.is_none());
+    }
+
+    #[test]
+    fn cost_of_the_box() {
+        let machine = Machine {
+            price_m2: Some(dec(10, 0)),
+            price_minute: Some(dec(2, 0)),
+            ..machine()
+        };
+        let mut p = crate::common::args::ModelParams::new("box-cuboid");
+        for (name, value) in [("l", "300"), ("w", "200"), ("h", "100"), ("notch", "15")] {
+            p.set(name, value);
+        }
+        let drawing = crate::draw_params(&p).unwrap();
+
+        let report = Report::new(&drawing, &machine);
+        let cost = report.cost.unwrap();
+        assert!(report.cut_length > report.bend_length && report.bend_length > 0.0);
+        assert!((cost.material - report.area * 10.0).abs() < 1e-9);
+        assert!((cost.machine - report.time / 30.0).abs() < 1e-9);
+        assert_eq!(report.to_json()["cost"]["material"], round(cost.material));
+    }
+}
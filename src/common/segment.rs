@@ -0,0 +1,159 @@
+//! Drawing primitives: straight lines, circular arcs and cubic Bezier curves.
+use std::f64::consts::TAU;
+
+use rust_decimal::Decimal;
+
+use super::{CutType, Line, Point};
+
+/// Curves are split into this number of straight pieces when exact shape is not needed
+pub const FLATTEN_STEPS: usize = 16;
+
+/// Circular arc, full circle when `from` and `to` are the same point
+#[derive(Debug, Clone, Copy)]
+pub struct Arc {
+    pub from: Point,
+    pub to: Point,
+    pub center: Point,
+    /// Direction as seen on the sheet
+    pub clockwise: bool,
+    pub tp: CutType,
+}
+
+impl Arc {
+    pub fn is_circle(&self) -> bool {
+        self.from.x == self.to.x && self.from.y == self.to.y
+    }
+
+    pub fn radius(&self) -> f64 {
+        let ((fx, fy), (cx, cy)) = (self.from.to_f64(), self.center.to_f64());
+        (fx - cx).hypot(fy - cy)
+    }
+
+    /// Start angle and signed sweep (radians), positive sweep goes clockwise on the sheet
+    pub fn angles(&self) -> (f64, f64) {
+        let ((fx, fy), (tx, ty), (cx, cy)) =
+            (self.from.to_f64(), self.to.to_f64(), self.center.to_f64());
+        let start = (fy - cy).atan2(fx - cx);
+
+        if self.is_circle() {
+            return (start, if self.clockwise { TAU } else { -TAU });
+        }
+
+        let mut sweep = (ty - cy).atan2(tx - cx) - start;
+        if self.clockwise && sweep <= 0.0 {
+            sweep += TAU;
+        } else if !self.clockwise && sweep >= 0.0 {
+            sweep -= TAU;
+        }
+        (start, sweep)
+    }
+}
+
+/// Cubic Bezier curve
+#[derive(Debug, Clone, Copy)]
+pub struct Cubic {
+    pub from: Point,
+    pub c1: Point,
+    pub c2: Point,
+    pub to: Point,
+    pub tp: CutType,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Segment {
+    Line(Line),
+    Arc(Arc),
+    Cubic(Cubic),
+}
+
+impl Segment {
+    pub fn tp(&self) -> CutType {
+        match self {
+            Segment::Line(l) => l.tp,
+            Segment::Arc(a) => a.tp,
+            Segment::Cubic(c) => c.tp,
+        }
+    }
+
+    pub fn from(&self) -> Point {
+        match self {
+            Segment::Line(l) => l.from,
+            Segment::Arc(a) => a.from,
+            Segment::Cubic(c) => c.from,
+        }
+    }
+
+    /// Points along the segment including both ends, curves are split into straight pieces
+    pub fn flatten(&self) -> Vec<(f64, f64)> {
+        match self {
+            Segment::Line(l) => vec![l.from.to_f64(), l.to.to_f64()],
+            Segment::Arc(a) => {
+                let (cx, cy) = a.center.to_f64();
+                let r = a.radius();
+                let (start, sweep) = a.angles();
+                (0..=FLATTEN_STEPS)
+                    .map(|i| {
+                        let (s, c) = (start + sweep * i as f64 / FLATTEN_STEPS as f64).sin_cos();
+                        (cx + r * c, cy + r * s)
+                    })
+                    .collect()
+            }
+            Segment::Cubic(b) => {
+                let pts = [b.from, b.c1, b.c2, b.to].map(|p| p.to_f64());
+                (0..=FLATTEN_STEPS)
+                    .map(|i| {
+                        let t = i as f64 / FLATTEN_STEPS as f64;
+                        let k = [
+                            (1.0 - t).powi(3),
+                            3.0 * (1.0 - t).powi(2) * t,
+                            3.0 * (1.0 - t) * t * t,
+                            t.powi(3),
+                        ];
+                        (0..4).fold((0.0, 0.0), |acc, j| {
+                            (acc.0 + pts[j].0 * k[j], acc.1 + pts[j].1 * k[j])
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+pub fn draw_arc(
+    from: Point,
+    to: Point,
+    center: Point,
+    clockwise: bool,
+    tp: &CutType,
+) -> Option<Arc> {
+    if !tp.is_cuttable() {
+        return None;
+    }
+
+    Some(Arc {
+        from,
+        to,
+        center,
+        clockwise,
+        tp: *tp,
+    })
+}
+
+pub fn draw_circle(center: Point, radius: Decimal, tp: &CutType) -> Option<Arc> {
+    let from = center.shift_x(radius);
+    draw_arc(from, from, center, true, tp)
+}
+
+pub fn draw_cubic(from: Point, c1: Point, c2: Point, to: Point, tp: &CutType) -> Option<Cubic> {
+    if !tp.is_cuttable() {
+        return None;
+    }
+
+    Some(Cubic {
+        from,
+        c1,
+        c2,
+        to,
+        tp: *tp,
+    })
+}
@@ -1,21 +1,23 @@
-use crate::common::{draw_line, CutType, DrawResult, Point};
+use rust_decimal::Decimal;
+
+use crate::common::{CutType, DrawResult, Hinge, Net, Panel, Point, Role, Side};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Square {
-    pub h: f64,
-    pub w: f64,
+    pub h: Decimal,
+    pub w: Decimal,
 }
 
 impl Square {
-    pub fn new(w: f64, h: f64) -> Self {
+    pub fn new(w: Decimal, h: Decimal) -> Self {
         Self { h, w }
     }
 
-    pub fn add_w(&mut self, delta: f64) {
+    pub fn add_w(&mut self, delta: Decimal) {
         self.w += delta;
     }
 
-    pub fn add_h(&mut self, delta: f64) {
+    pub fn add_h(&mut self, delta: Decimal) {
         self.h += delta;
     }
 }
@@ -54,113 +56,174 @@ impl Borders {
 pub struct SquareElement {
     borders: Borders,
     pub square: Square,
+    role: Role,
+    name: Option<&'static str>,
+    hinge: Option<(&'static str, Side, f64)>,
+    /// Radius of rounded corners
+    radius: Decimal,
+    /// Half circle cut in the middle of the side
+    notch: Option<(Side, Decimal)>,
 }
 
 impl SquareElement {
-    pub fn new(w: f64, h: f64) -> Self {
+    pub fn new(w: Decimal, h: Decimal) -> Self {
         Self {
             borders: Borders::nope(),
             square: Square::new(w, h),
+            role: Role::Mark,
+            name: None,
+            hinge: None,
+            radius: Decimal::ZERO,
+            notch: None,
         }
     }
 
-    pub fn cut(w: f64, h: f64) -> Self {
+    /// Cut all around, by default it is a piece removed from the sheet
+    pub fn cut(w: Decimal, h: Decimal) -> Self {
         Self {
             borders: Borders::new_cut(),
             square: Square::new(w, h),
+            role: Role::CutOff,
+            name: None,
+            hinge: None,
+            radius: Decimal::ZERO,
+            notch: None,
         }
     }
 
+    pub fn role(&self, role: Role) -> Self {
+        let mut new = *self;
+        new.role = role;
+        new
+    }
+
+    /// Name of the panel in the box net
+    pub fn named(&self, name: &'static str) -> Self {
+        let mut new = *self;
+        new.name = Some(name);
+        new
+    }
+
+    /// Attach `side` of the element to the `parent` panel folded by `angle` degrees
+    pub fn hinged(&self, parent: &'static str, side: Side, angle: f64) -> Self {
+        let mut new = *self;
+        new.hinge = Some((parent, side, angle));
+        new
+    }
+
+    /// Round the corners, radius is limited by the half of the shorter side
+    pub fn rounded(&self, radius: Decimal) -> Self {
+        let mut new = *self;
+        new.radius = radius;
+        new
+    }
+
+    /// Thumb notch of `radius` in the middle of the `side`, the side must be longer than the diameter
+    pub fn notch(&self, side: Side, radius: Decimal) -> Self {
+        let mut new = *self;
+        new.notch = Some((side, radius));
+        new
+    }
+
     pub fn with_borders(&self, borders: Borders) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders = borders;
         new
     }
     pub fn borders(&self, top: CutType, right: CutType, bottom: CutType, left: CutType) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders = Borders::new(top, right, bottom, left);
         new
     }
 
     pub fn border_top(&self, tp: CutType) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders.top = tp;
         new
     }
 
     pub fn border_right(&self, tp: CutType) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders.right = tp;
         new
     }
 
     pub fn border_bottom(&self, tp: CutType) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders.bottom = tp;
         new
     }
 
     pub fn border_left(&self, tp: CutType) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.borders.left = tp;
         new
     }
 
-    pub fn height(&self, h: f64) -> Self {
-        let mut new = self.clone();
+    pub fn height(&self, h: Decimal) -> Self {
+        let mut new = *self;
         new.square.h = h;
         new
     }
 
-    pub fn width(&self, w: f64) -> Self {
-        let mut new = self.clone();
-        new.square.w = w;
-        new
-    }
-
     pub fn mirror_vertical(&self) -> Self {
-        let mut mirrored = self.clone();
+        let flip = |side| match side {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+            other => other,
+        };
+        let mut mirrored = *self;
         mirrored.borders.left = self.borders.right;
         mirrored.borders.right = self.borders.left;
+        mirrored.hinge = self
+            .hinge
+            .map(|(parent, side, angle)| (parent, flip(side), angle));
+        mirrored.notch = self.notch.map(|(side, radius)| (flip(side), radius));
         mirrored
     }
 
     pub fn mirror_horisontal(&self) -> Self {
-        let mut mirrored = self.clone();
+        let flip = |side| match side {
+            Side::Top => Side::Bottom,
+            Side::Bottom => Side::Top,
+            other => other,
+        };
+        let mut mirrored = *self;
         mirrored.borders.top = self.borders.bottom;
         mirrored.borders.bottom = self.borders.top;
+        mirrored.hinge = self
+            .hinge
+            .map(|(parent, side, angle)| (parent, flip(side), angle));
+        mirrored.notch = self.notch.map(|(side, radius)| (flip(side), radius));
         mirrored
     }
 
     pub fn draw(&self, offset: Point) -> DrawResult {
-        let mut paths = Vec::new();
-        let mut from = offset.align_top_left(self.square);
+        let from = offset.align_top_left(self.square);
+
+        let (mut panel, sides) = if self.radius > Decimal::ZERO {
+            Panel::rounded_rect(self.role, from, self.square, self.borders, self.radius)
+        } else {
+            let panel = Panel::rect(self.role, from, self.square, self.borders);
+            (panel, [0, 1, 2, 3])
+        };
+        panel.name = self.name.map(String::from);
+        panel.hinge = self.hinge.map(|(parent, side, angle)| Hinge {
+            parent: parent.to_string(),
+            edge: sides[side.edge()],
+            angle,
+        });
+        if let Some((side, radius)) = self.notch {
+            panel.notch(sides[side.edge()], radius);
+        }
+
         let mut max = from;
+        for v in panel.vertices.iter() {
+            max.update_max(*v);
+        }
 
-        // TOP
-        let to = from.shift_xy(self.square.w, 0.0);
-        paths.push(draw_line(from, to, &self.borders.top));
-        max.update_max(to);
-        from = to;
-
-        // RIGHT
-        let to = from.shift_xy(0.0, self.square.h);
-        paths.push(draw_line(from, to, &self.borders.right));
-        max.update_max(to);
-        from = to;
-
-        // BOTTOM
-        let to = from.shift_xy(self.square.w * -1.0, 0.0);
-        paths.push(draw_line(from, to, &self.borders.bottom));
-        max.update_max(to);
-        from = to;
-
-        // LEFT
-        let to = from.shift_xy(0.0, self.square.h * -1.0);
-        paths.push(draw_line(from, to, &self.borders.left));
-        max.update_max(to);
-        // from = to;
-
-        DrawResult::new(paths.into_iter().filter_map(|v| v).collect(), max)
+        let mut net = Net::default();
+        net.add(panel);
+        DrawResult::new(net, max)
     }
 }
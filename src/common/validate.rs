@@ -0,0 +1,457 @@
+//! Checks that the folded net closes: hinged edges meet, glue flaps lie on their targets
+//! and flaps do not go through other panels.
+use std::fmt;
+
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+
+use super::fold::{fold, FoldedPanel, Vec3};
+use super::{Net, Point, Role};
+
+/// Allowed geometry mismatch (mm)
+pub const TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FoldIssue {
+    /// Hinge edge of the panel does not lie on the edge of the parent
+    Hinge { panel: String, parent: String },
+    /// Glue flap is not parallel to the target after folding
+    GlueAngle { flap: String, target: String },
+    /// Gap between the glue flap and the target (mm)
+    GlueGap {
+        flap: String,
+        target: String,
+        gap: f64,
+    },
+    /// Glue flap sticks out of the target (mm)
+    GlueOverhang {
+        flap: String,
+        target: String,
+        overhang: f64,
+    },
+    /// Flap goes into another panel (mm)
+    Overlap {
+        flap: String,
+        other: String,
+        depth: f64,
+    },
+}
+
+impl fmt::Display for FoldIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoldIssue::Hinge { panel, parent } => {
+                write!(f, "Сгиб панели {panel} не лежит на краю {parent}")
+            }
+            FoldIssue::GlueAngle { flap, target } => {
+                write!(f, "Клапан {flap} не ложится на {target}")
+            }
+            FoldIssue::GlueGap { flap, target, gap } => {
+                write!(f, "Клапан {flap} не достает до {target} на {gap:.2}мм")
+            }
+            FoldIssue::GlueOverhang {
+                flap,
+                target,
+                overhang,
+            } => {
+                write!(f, "Клапан {flap} вылезает за {target} на {overhang:.2}мм")
+            }
+            FoldIssue::Overlap { flap, other, depth } => {
+                write!(f, "Клапан {flap} залезает в {other} на {depth:.2}мм")
+            }
+        }
+    }
+}
+
+/// Fold the net and collect everything that prevents the box from closing
+pub fn check_fold(net: &Net) -> Result<Vec<FoldIssue>> {
+    let folded = fold(net)?;
+    let thickness = |f: &FoldedPanel| net.thickness_of(f.panel).to_f64().unwrap_or_default();
+    let mut issues = Vec::new();
+
+    for f in folded.iter() {
+        let Some(h) = f.panel.hinge.as_ref() else {
+            continue;
+        };
+        let Some(parent) = net.panel(&h.parent) else {
+            continue;
+        };
+        let (a, b) = f.panel.edge(h.edge);
+        let on_edge = (0..parent.vertices.len()).any(|i| {
+            let (c, d) = parent.edge(i);
+            edges_meet((a, b), (c, d))
+        });
+        if !on_edge {
+            issues.push(FoldIssue::Hinge {
+                panel: name(f),
+                parent: h.parent.clone(),
+            });
+        }
+    }
+
+    let find = |name: &str| folded.iter().find(|f| f.panel.is_named(name));
+
+    for pair in net.glue.iter() {
+        let (Some(flap), Some(target)) = (find(&pair.flap), find(&pair.target)) else {
+            continue;
+        };
+        issues.extend(check_glue(flap, target, thickness(flap), thickness(target)));
+    }
+
+    let solids: Vec<_> = folded
+        .iter()
+        .filter(|f| f.panel.role.is_folded())
+        .map(|f| (f, Prism::new(f, thickness(f))))
+        .collect();
+
+    for (f, prism) in solids.iter() {
+        if !matches!(f.panel.role, Role::Flap | Role::GlueFlap) {
+            continue;
+        }
+        for (other, other_prism) in solids.iter() {
+            if std::ptr::eq(f.panel, other.panel) {
+                continue;
+            }
+            let depth = prism.penetration(other_prism);
+            if depth > TOLERANCE {
+                issues.push(FoldIssue::Overlap {
+                    flap: name(f),
+                    other: name(other),
+                    depth,
+                });
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+fn name(f: &FoldedPanel) -> String {
+    f.panel.name.clone().unwrap_or_default()
+}
+
+/// Segments are on the same line and the shorter one is inside the longer one
+fn edges_meet(a: (Point, Point), b: (Point, Point)) -> bool {
+    let (a, b) = ((a.0.to_f64(), a.1.to_f64()), (b.0.to_f64(), b.1.to_f64()));
+    let len = |s: ((f64, f64), (f64, f64))| (s.1 .0 - s.0 .0).hypot(s.1 .1 - s.0 .1);
+    let (short, long) = if len(a) <= len(b) { (a, b) } else { (b, a) };
+    let l = len(long);
+    if l == 0.0 {
+        return false;
+    }
+    let (ux, uy) = ((long.1 .0 - long.0 .0) / l, (long.1 .1 - long.0 .1) / l);
+
+    [short.0, short.1].iter().all(|p| {
+        let (dx, dy) = (p.0 - long.0 .0, p.1 - long.0 .1);
+        let along = dx * ux + dy * uy;
+        let across = (dx * uy - dy * ux).abs();
+        across <= TOLERANCE && along >= -TOLERANCE && along <= l + TOLERANCE
+    })
+}
+
+fn check_glue(
+    flap: &FoldedPanel,
+    target: &FoldedPanel,
+    flap_thickness: f64,
+    thickness: f64,
+) -> Vec<FoldIssue> {
+    let n = target.normal();
+    let names = || (name(flap), name(target));
+
+    if flap.normal().dot(n).abs() < 1.0 - 1e-6 {
+        let (flap, target) = names();
+        return vec![FoldIssue::GlueAngle { flap, target }];
+    }
+
+    let mut issues = Vec::new();
+
+    let (flo, fhi) = Prism::new(flap, flap_thickness).project(n);
+    let (tlo, thi) = Prism::new(target, thickness).project(n);
+    let gap = (flo - thi).max(tlo - fhi);
+    if gap > TOLERANCE {
+        let (flap, target) = names();
+        issues.push(FoldIssue::GlueGap { flap, target, gap });
+    }
+
+    // Flap could wrap around the board edge of the target, so allow one thickness
+    let tv = target.vertices();
+    let inward: Vec<Vec3> = (0..tv.len())
+        .map(|i| {
+            let (a, b) = target.panel.edge(i);
+            let axis = (Vec3::on_sheet(b) - Vec3::on_sheet(a)).normalize();
+            target.transform.apply_dir(Vec3::new(-axis.y, axis.x, 0.0))
+        })
+        .collect();
+
+    let overhang = flap
+        .vertices()
+        .into_iter()
+        .map(|p| {
+            (0..tv.len())
+                .map(|i| -(p - tv[i]).dot(inward[i]))
+                .fold(f64::MIN, f64::max)
+        })
+        .fold(0.0, f64::max)
+        - thickness;
+
+    if overhang > TOLERANCE {
+        let (flap, target) = names();
+        issues.push(FoldIssue::GlueOverhang {
+            flap,
+            target,
+            overhang,
+        });
+    }
+
+    issues
+}
+
+/// Folded panel extruded by the board thickness
+struct Prism {
+    vertices: Vec<Vec3>,
+    /// Face normals and edge directions, enough for the separating axis test
+    normals: Vec<Vec3>,
+    edges: Vec<Vec3>,
+}
+
+impl Prism {
+    fn new(f: &FoldedPanel, thickness: f64) -> Self {
+        let top = f.vertices();
+        let n = f.normal();
+        let shift = n * -thickness;
+
+        let mut edges = vec![n];
+        let mut normals = vec![n];
+        for i in 0..top.len() {
+            let e = (top[(i + 1) % top.len()] - top[i]).normalize();
+            edges.push(e);
+            normals.push(n.cross(e).normalize());
+        }
+
+        let mut vertices = top.clone();
+        vertices.extend(top.iter().map(|v| *v + shift));
+
+        Self {
+            vertices,
+            normals,
+            edges,
+        }
+    }
+
+    fn project(&self, axis: Vec3) -> (f64, f64) {
+        self.vertices
+            .iter()
+            .map(|v| v.dot(axis))
+            .fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)))
+    }
+
+    /// How deep solids go into each other, zero or less if they just touch or are apart
+    fn penetration(&self, other: &Prism) -> f64 {
+        let mut axes: Vec<Vec3> = self
+            .normals
+            .iter()
+            .chain(other.normals.iter())
+            .copied()
+            .collect();
+        for a in self.edges.iter() {
+            for b in other.edges.iter() {
+                let c = a.cross(*b);
+                if c.len() > 1e-6 {
+                    axes.push(c.normalize());
+                }
+            }
+        }
+
+        axes.into_iter()
+            .map(|axis| {
+                let (alo, ahi) = self.project(axis);
+                let (blo, bhi) = other.project(axis);
+                ahi.min(bhi) - alo.max(blo)
+            })
+            .fold(f64::MAX, f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::args::ModelParams;
+    use crate::common::{dec, Borders, Side, SquareElement};
+    use rust_decimal::Decimal;
+
+    fn issues(model: &str, params: &[(&str, String)]) -> Vec<String> {
+        let mut p = ModelParams::new(model);
+        for (name, value) in params {
+            p.set(name, value);
+        }
+        let drawing = crate::draw_params(&p).unwrap();
+        check_fold(&drawing.net)
+            .unwrap()
+            .into_iter()
+            .map(|i| format!("{model} {params:?}: {i}"))
+            .collect()
+    }
+
+    const THICKNESS: [&str; 3] = ["1", "2.3", "5"];
+    const GLUE_FLAP: [&str; 2] = ["20", "40"];
+    const FLAP_STYLE: [&str; 4] = ["square", "chamfer", "trapezoid", "rounded"];
+    const FAT: [&str; 2] = ["false", "true"];
+
+    /// Glue flap length, flap style and double lid walls
+    fn flaps() -> impl Iterator<Item = (&'static str, &'static str, &'static str)> {
+        GLUE_FLAP.into_iter().flat_map(|g| {
+            FLAP_STYLE
+                .into_iter()
+                .flat_map(move |style| FAT.into_iter().map(move |fat| (g, style, fat)))
+        })
+    }
+
+    #[test]
+    fn box_cuboid_closes() {
+        let mut all = Vec::new();
+        for l in ["150", "300", "600"] {
+            for w in ["100", "250"] {
+                for h in ["60", "150"] {
+                    for t in THICKNESS {
+                        for (g, style, fat) in flaps() {
+                            all.extend(issues(
+                                "box-cuboid",
+                                &[
+                                    ("length", l.into()),
+                                    ("width", w.into()),
+                                    ("height", h.into()),
+                                    ("lid", "35".into()),
+                                    ("thickness", t.into()),
+                                    ("glueflap", g.into()),
+                                    ("flap-style", style.into()),
+                                    ("lid-fat", fat.into()),
+                                ],
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(all.is_empty(), "{}", all.join("\n"));
+    }
+
+    #[test]
+    fn lid_closes() {
+        let mut all = Vec::new();
+        for l in ["150", "300", "600"] {
+            for w in ["100", "250"] {
+                for h in ["30", "60"] {
+                    for t in THICKNESS {
+                        for (g, style, fat) in flaps() {
+                            all.extend(issues(
+                                "lid",
+                                &[
+                                    ("length", l.into()),
+                                    ("width", w.into()),
+                                    ("height", h.into()),
+                                    ("thickness", t.into()),
+                                    ("glueflap", g.into()),
+                                    ("flap-style", style.into()),
+                                    ("fat", fat.into()),
+                                ],
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(all.is_empty(), "{}", all.join("\n"));
+    }
+
+    #[test]
+    fn vinyl_closes() {
+        let mut all = Vec::new();
+        for w in ["60", "100", "200"] {
+            for lid in ["35", "60"] {
+                for t in THICKNESS {
+                    for (g, style, fat) in flaps() {
+                        all.extend(issues(
+                            "vinyl",
+                            &[
+                                ("width", w.into()),
+                                ("lid", lid.into()),
+                                ("thickness", t.into()),
+                                ("glueflap", g.into()),
+                                ("flap-style", style.into()),
+                                ("lid-fat", fat.into()),
+                            ],
+                        ));
+                    }
+                }
+            }
+        }
+        assert!(all.is_empty(), "{}", all.join("\n"));
+    }
+
+    fn mm(v: u32) -> Decimal {
+        dec(v, 0)
+    }
+
+    /// Bottom with inset back wall, front and right walls, back flap glued to the right wall
+    fn corner(flap_shift: u32, flap_w: u32) -> Net {
+        let mut net = Net::new(mm(2));
+        let wall_w = |w| {
+            SquareElement::new(mm(w), mm(50))
+                .with_borders(Borders::new_cut())
+                .role(Role::Wall)
+        };
+        let wall = wall_w(100);
+        net.append(
+            wall.role(Role::Bottom)
+                .named("bottom")
+                .draw(Point::new(mm(0), mm(50)))
+                .net,
+        );
+        net.append(
+            wall_w(96)
+                .named("back")
+                .hinged("bottom", Side::Bottom, 90.0)
+                .draw(Point::new(mm(2), mm(0)))
+                .net,
+        );
+        net.append(
+            wall.named("front")
+                .hinged("bottom", Side::Top, 90.0)
+                .draw(Point::new(mm(0), mm(100)))
+                .net,
+        );
+        net.append(
+            wall_w(50)
+                .named("right")
+                .hinged("bottom", Side::Left, 90.0)
+                .draw(Point::new(mm(100), mm(50)))
+                .net,
+        );
+        net.append(
+            SquareElement::new(mm(flap_w), mm(50))
+                .role(Role::GlueFlap)
+                .named("flap")
+                .hinged("back", Side::Left, 90.0)
+                .draw(Point::new(mm(98), mm(flap_shift)))
+                .net,
+        );
+        net.glue("flap", "right");
+        net
+    }
+
+    #[test]
+    fn detects_broken_nets() {
+        assert_eq!(check_fold(&corner(0, 30)).unwrap(), vec![]);
+
+        let issues = check_fold(&corner(5, 30)).unwrap();
+        assert!(issues.iter().any(|i| matches!(i, FoldIssue::Hinge { .. })));
+
+        let issues = check_fold(&corner(0, 80)).unwrap();
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, FoldIssue::GlueOverhang { .. })));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, FoldIssue::Overlap { .. })));
+    }
+}
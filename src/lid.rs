@@ -1,10 +1,14 @@
 use anyhow::Result;
-use clap::{value_parser, Arg, ArgMatches, Command};
-use rust_decimal::{prelude::ToPrimitive, Decimal};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rust_decimal::Decimal;
 
 use crate::common::{
-    args::{cli_help_arg, GlueFlap, Height, Length, Thickness, Width},
-    Borders, CutType, DrawResult, Origin, Point, SquareElement, VIEWPORT_OFFSET,
+    args::{
+        cli_help_arg, GlueFlap, GlueFlapAngle, GlueFlapStyle, Height, Length, Thickness,
+        ThumbNotch, Width,
+    },
+    dec, file_name, Borders, CutType, DrawResult, FlapStyle, Net, Notch, NotchPlace, Origin, Point,
+    PolygonElement, Role, Side, Square, SquareElement, VIEWPORT_OFFSET,
 };
 
 const FILE_NAME_DEFAULT: &str = "LaserCutLid.svg";
@@ -30,15 +34,38 @@ impl LidHeight {
     }
 }
 
+pub struct LidKind;
+impl LidKind {
+    const NAME: &'static str = "type";
+
+    const DEFAULT: &'static str = "separated";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("type")
+            .value_parser(["joined", "glued", "separated"])
+            .default_value(Self::DEFAULT)
+            .help("Тип крышки: separated - отдельная с четырьмя бортами, glued - без заднего борта, приклеивается полосой к задней стенке коробки, joined - без заднего борта и полосы, самая дешевая.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> LidType {
+        match m.get_one::<String>(Self::NAME).map(|v| v.as_str()) {
+            Some("joined") => LidType::Joined,
+            Some("glued") => LidType::Glued,
+            _ => LidType::Separated,
+        }
+    }
+}
+
 pub struct LidBorders;
 impl LidBorders {
-    const NAME: &'static str = "lid-fat";
+    const NAME: &'static str = "lidfat";
 
     pub fn arg() -> Arg {
         Arg::new(Self::NAME)
             .long("lid-fat")
-            .required(false)
-            .help("Толстые (двойные) борты у крышки.")
+            .action(ArgAction::SetTrue)
+            .help("Толстые (двойные) борты у крышки, внутренний слой боковых бортов фиксируется язычками в прорезях.")
     }
 
     pub fn extract(m: &ArgMatches) -> bool {
@@ -56,26 +83,35 @@ pub fn cli_build(root: Command) -> Command {
         .arg(Length::arg().help("Наружная длинна (мм). Тут может быть склейка."))
         .arg(Thickness::arg())
         .arg(GlueFlap::arg())
-        .arg(
-            Arg::new("fat")
-                .long("fat")
-                .num_args(0)
-                .help("Толстые (двойные) борты у крышки."),
-        );
+        .arg(GlueFlapStyle::arg())
+        .arg(GlueFlapAngle::arg())
+        .arg(LidKind::arg())
+        .arg(ThumbNotch::arg())
+        .arg(LidBorders::arg().long("fat"));
 
     root.subcommand(c)
 }
 
 pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
     let lid = LidForBox {
-        ltype: LidType::Separated,
-        height: Height::extract(m).unwrap().to_f64().unwrap(),
-        box_outer_width: Width::extract(m).unwrap().to_f64().unwrap(),
-        box_outer_length: Length::extract(m).unwrap().to_f64().unwrap(),
-        thickness: Thickness::extract(m).unwrap().to_f64().unwrap(),
-        glue_flap: GlueFlap::extract(m).unwrap().to_f64().unwrap(),
-        fat_border: m.get_flag("fat"),
-        result: DrawResult::empty(FILE_NAME_DEFAULT.to_string()),
+        ltype: LidKind::extract(m),
+        height: Height::extract(m).unwrap(),
+        box_outer_width: Width::extract(m).unwrap(),
+        box_outer_length: Length::extract(m).unwrap(),
+        thickness: Thickness::extract(m).unwrap(),
+        glue_flap: GlueFlap::extract(m)?,
+        flap_style: GlueFlapStyle::extract(m)?,
+        fat_border: LidBorders::extract(m),
+        notch: Notch::placed_at(m, NotchPlace::Lid)?,
+        result: DrawResult::empty(file_name(
+            CLI_SUBCOMMAND,
+            [
+                Length::extract(m).unwrap(),
+                Width::extract(m).unwrap(),
+                Height::extract(m).unwrap(),
+            ],
+            Thickness::extract(m).unwrap(),
+        )),
     };
 
     log::info!("Крышка для коробок в работе");
@@ -83,26 +119,175 @@ pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
     Ok(lid.draw())
 }
 
+/// Width of the tabs locking the inner walls of the fat lid
+const LOCK_TAB_W: Decimal = dec(20, 0);
+
+/// Inner layers of the fat lid side walls. They are folded back inside the walls,
+/// two tabs on the free edge of each layer lock in the slots of the top.
+pub struct InnerWalls {
+    /// Side wall of the lid, width is across the hinge to the top
+    pub wall: Square,
+    /// Space left at the front and back ends of the wall for the layers of other walls
+    pub gaps: (Decimal, Decimal),
+    pub thickness: Decimal,
+}
+
+impl InnerWalls {
+    fn length(&self) -> Decimal {
+        self.wall.h - self.gaps.0 - self.gaps.1
+    }
+
+    /// Tab centers along the layer and the tab width
+    fn tabs(&self) -> ([Decimal; 2], Decimal) {
+        let quarter = self.length() / dec(4, 0);
+        ([quarter, quarter * dec(3, 0)], LOCK_TAB_W.min(quarter))
+    }
+
+    /// Layer of the left wall attached by the right edge, the tabs stick out on the left
+    fn layer(&self) -> PolygonElement {
+        let (h, l, t) = (self.wall.w - self.thickness, self.length(), self.thickness);
+        let (centers, tab_w) = self.tabs();
+
+        let mut vertices = vec![
+            Point::new(Decimal::ZERO, Decimal::ZERO),
+            Point::new(h, Decimal::ZERO),
+            Point::new(h, l),
+            Point::new(Decimal::ZERO, l),
+        ];
+        let mut edges = vec![CutType::Cut, CutType::Bend, CutType::Cut, CutType::Cut];
+        // Up the free edge
+        for c in centers.iter().rev() {
+            let (y0, y1) = (*c - tab_w / Decimal::TWO, *c + tab_w / Decimal::TWO);
+            vertices.extend([
+                Point::new(Decimal::ZERO, y1),
+                Point::new(-t, y1),
+                Point::new(-t, y0),
+                Point::new(Decimal::ZERO, y0),
+            ]);
+            edges.extend([CutType::Cut; 4]);
+        }
+
+        PolygonElement::new(&vertices, &edges)
+            .expect("Layer outline has the tabs")
+            .role(Role::Flap)
+    }
+
+    /// Layers, cut offs at the ends of the wall edges and slots in the top.
+    /// `left` and `right` are the front ends of the hinges between the side walls and the top,
+    /// `names` are the names of the walls and their layers.
+    pub fn draw(
+        &self,
+        left: Point,
+        right: Point,
+        names: [(&'static str, &'static str); 2],
+    ) -> DrawResult {
+        let t = self.thickness;
+        let layer = self.layer();
+        let (centers, tab_w) = self.tabs();
+        let mut result = DrawResult::new(Net::default(), Point::zero());
+
+        let [(left_wall, left_layer), (right_wall, right_layer)] = names;
+        let left_edge = left.shift_nx(self.wall.w);
+        let right_edge = right.shift_x(self.wall.w);
+
+        result.append(
+            layer
+                .named(left_layer)
+                .hinged_side(left_wall, Side::Right, 180.0)
+                .draw(left_edge.shift_y(self.gaps.0).origin(Origin::TopRight)),
+        );
+        result.append(
+            layer
+                .mirror_vertical()
+                .named(right_layer)
+                .hinged_side(right_wall, Side::Left, 180.0)
+                .draw(right_edge.shift_y(self.gaps.0)),
+        );
+
+        // Free edges of the walls beside the layers
+        for (gap, y) in [
+            (self.gaps.0, Decimal::ZERO),
+            (self.gaps.1, self.wall.h - self.gaps.1),
+        ] {
+            if gap.is_zero() {
+                continue;
+            }
+            let cut = SquareElement::cut(t, gap);
+            result.append(cut.draw(left_edge.shift_y(y).origin(Origin::TopRight)));
+            result.append(cut.draw(right_edge.shift_y(y)));
+        }
+
+        // The layers stand next to the walls, the tabs go through the top
+        let slot = SquareElement::cut(t, tab_w).role(Role::Hole);
+        for c in centers {
+            let y = self.gaps.0 + c - tab_w / Decimal::TWO;
+            result.append(slot.draw(left.shift_xy(t, y)));
+            result.append(slot.draw(right.shift_xy(-t, y).origin(Origin::TopRight)));
+        }
+
+        result
+    }
+}
+
+/// How the back of the lid is made, the lid is wider by the layers of cardboard at the back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LidType {
+    /// No back wall, the top lies on the back wall of the box: width + 2t
     Joined,
+    /// No back wall, the strip behind the top is glued to the back wall of the box: width + 3t
     Glued,
+    /// Four walls, put on the box: width + 4t
     Separated,
 }
 
 pub struct LidForBox {
     ltype: LidType,
-    height: f64,
-    box_outer_width: f64,
-    box_outer_length: f64,
-    thickness: f64,
-    glue_flap: f64,
+    height: Decimal,
+    box_outer_width: Decimal,
+    box_outer_length: Decimal,
+    thickness: Decimal,
+    glue_flap: Decimal,
+    flap_style: FlapStyle,
     fat_border: bool,
+    /// Thumb notch on the front skirt
+    notch: Option<Notch>,
     result: DrawResult,
 }
 
 impl LidForBox {
-    pub fn thick_n(&self, multiply: usize) -> f64 {
-        self.thickness * multiply as f64
+    /// Lid with four walls for the box of the outer size
+    pub fn separated(
+        box_outer_length: Decimal,
+        box_outer_width: Decimal,
+        height: Decimal,
+        thickness: Decimal,
+        glue_flap: Decimal,
+        flap_style: FlapStyle,
+    ) -> Self {
+        Self {
+            ltype: LidType::Separated,
+            height,
+            box_outer_width,
+            box_outer_length,
+            thickness,
+            glue_flap,
+            flap_style,
+            fat_border: false,
+            notch: None,
+            result: DrawResult::empty(FILE_NAME_DEFAULT.to_string()),
+        }
+    }
+
+    pub fn fat(self, fat_border: bool) -> Self {
+        Self { fat_border, ..self }
+    }
+
+    pub fn notch(self, notch: Option<Notch>) -> Self {
+        Self { notch, ..self }
+    }
+
+    pub fn thick_n(&self, multiply: usize) -> Decimal {
+        self.thickness * Decimal::from(multiply)
     }
 
     fn square_cut(&self) -> SquareElement {
@@ -110,13 +295,28 @@ impl LidForBox {
     }
 
     fn draw(self) -> DrawResult {
-        let mut offset = Point::new(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
-
-        offset = offset.shift_x(self.height * if self.fat_border { 2.0 } else { 1.0 });
+        let offset = self.start(VIEWPORT_OFFSET);
+        self.draw_from(offset).1
+    }
 
+    /// Lid on the same sheet under the `other` drawing
+    pub fn draw_below(self, other: &DrawResult) -> DrawResult {
+        let offset = self.start(other.max.y + VIEWPORT_OFFSET);
         self.draw_from(offset).1
     }
+
+    /// Top left corner of the lid drawing at `y`
+    fn start(&self, y: Decimal) -> Point {
+        // Side walls with the inner layers and their tabs are on the left
+        Point::new(VIEWPORT_OFFSET, y).shift_x(if self.fat_border {
+            self.height * Decimal::TWO + self.thick_n(2)
+        } else {
+            self.height
+        })
+    }
     fn draw_from(mut self, offset: Point) -> (Point, DrawResult) {
+        self.result.net = Net::new(self.thickness);
+
         let lid_len = self.box_outer_length + self.thick_n(if self.fat_border { 4 } else { 2 });
         let lid_width = self.box_outer_width
             + match self.ltype {
@@ -125,12 +325,31 @@ impl LidForBox {
                 LidType::Separated => self.thick_n(4),
             };
 
-        let long_side_flap = SquareElement::new(lid_len - self.glue_flap * 2.0, self.height)
-            .with_borders(Borders::new_cut())
-            .border_bottom(CutType::Bend);
+        let long_side_flap =
+            SquareElement::new(lid_len - self.glue_flap * Decimal::TWO, self.height)
+                .with_borders(Borders::new_cut())
+                .border_bottom(CutType::Bend)
+                .role(Role::Flap);
 
-        self.result
-            .append(long_side_flap.draw(offset.shift_x(self.glue_flap)));
+        self.result.append(
+            long_side_flap
+                .named("front-flap")
+                .hinged("front", Side::Bottom, 180.0)
+                .draw(offset.shift_x(self.glue_flap)),
+        );
+
+        let notch = self.notch.and_then(|n| {
+            n.placed(
+                NotchPlace::Lid,
+                long_side_flap.square.w,
+                long_side_flap.square.h,
+            )
+        });
+        if let Some(notch) = notch {
+            self.result.append(
+                notch.draw_hole(offset.shift_xy(lid_len / Decimal::TWO, long_side_flap.square.h)),
+            );
+        }
 
         let glue_flap_side_cut = SquareElement::new(self.glue_flap, long_side_flap.square.h)
             .with_borders(Borders::nope())
@@ -143,19 +362,28 @@ impl LidForBox {
 
         let offset = offset.shift_y(long_side_flap.square.h);
 
-        let long_side = SquareElement::new(lid_len, self.height + self.thickness).borders(
-            CutType::Nope,
-            CutType::Cut,
-            CutType::Nope,
-            CutType::Cut,
-        );
+        let long_side = SquareElement::new(lid_len, self.height + self.thickness)
+            .borders(CutType::Nope, CutType::Cut, CutType::Nope, CutType::Cut)
+            .role(Role::Wall);
 
-        self.result.append(long_side.draw(offset));
+        self.result.append(
+            long_side
+                .named("front")
+                .hinged("top", Side::Bottom, 90.0)
+                .draw(offset),
+        );
 
         let mut offset = offset.shift_y(long_side.square.h);
 
-        let top_wall = SquareElement::new(lid_len, lid_width).with_borders(Borders::new_bend());
-        self.result.append(top_wall.draw(offset));
+        let top_wall = SquareElement::new(lid_len, lid_width)
+            .with_borders(Borders::new_bend())
+            .role(Role::Top);
+        // Nothing is attached behind the joined lid
+        let top_wall = match self.ltype {
+            LidType::Joined => top_wall.border_bottom(CutType::Cut),
+            _ => top_wall,
+        };
+        self.result.append(top_wall.named("top").draw(offset));
 
         // Small top cuts
         self.result
@@ -183,96 +411,177 @@ impl LidForBox {
             );
         }
 
-        let side_flap = SquareElement::new(self.height, self.glue_flap).borders(
-            CutType::Cut,
-            CutType::Cut,
-            CutType::Bend,
-            CutType::Cut,
-        );
+        let side_flap = self
+            .flap_style
+            .flap(
+                Square::new(self.height, self.glue_flap),
+                Borders::new(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut),
+                Side::Bottom,
+            )
+            .map(|f| f.role(Role::GlueFlap));
 
-        self.result.append(
-            side_flap.draw(
-                offset
-                    .shift_nx(self.thickness)
-                    .shift_y(self.thickness)
-                    .origin(Origin::BottomRight),
-            ),
-        );
+        if let Some(side_flap) = &side_flap {
+            self.result.append(
+                side_flap
+                    .named("left-flap")
+                    .hinged_side("left", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_nx(self.thickness)
+                            .shift_y(self.thickness)
+                            .origin(Origin::BottomRight),
+                    ),
+            );
 
-        self.result.append(
-            side_flap.mirror_vertical().draw(
-                offset
-                    .shift_xy(lid_len + self.thickness, self.thickness)
-                    .origin(Origin::BottomLeft),
-            ),
-        );
+            self.result.append(
+                side_flap
+                    .mirror_vertical()
+                    .named("right-flap")
+                    .hinged_side("right", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_xy(lid_len + self.thickness, self.thickness)
+                            .origin(Origin::BottomLeft),
+                    ),
+            );
+        }
+
+        self.result.net.glue("left-flap", "front");
+        self.result.net.glue("right-flap", "front");
 
         let side_wall_h = match self.ltype {
             LidType::Joined | LidType::Glued => lid_width - self.thickness,
             LidType::Separated => lid_width - self.thick_n(2),
         };
 
-        let side_wall = SquareElement::new(self.height + self.thickness, side_wall_h).borders(
-            CutType::Nope,
-            CutType::Nope,
-            if let LidType::Separated = self.ltype {
-                CutType::Nope
-            } else {
-                CutType::Cut
-            },
-            CutType::Cut,
-        );
+        let side_wall = SquareElement::new(self.height + self.thickness, side_wall_h)
+            .borders(
+                CutType::Nope,
+                CutType::Nope,
+                if let LidType::Separated = self.ltype {
+                    CutType::Nope
+                } else {
+                    CutType::Cut
+                },
+                // Inner layer is attached to the free edge of the fat wall
+                if self.fat_border {
+                    CutType::Nope
+                } else {
+                    CutType::Cut
+                },
+            )
+            .role(Role::Wall)
+            .hinged("top", Side::Right, 90.0);
+
+        if self.fat_border {
+            let inner = InnerWalls {
+                wall: side_wall.square,
+                gaps: (
+                    self.thickness,
+                    match self.ltype {
+                        LidType::Joined => Decimal::ZERO,
+                        _ => self.thickness,
+                    },
+                ),
+                thickness: self.thickness,
+            };
+            self.result.append(inner.draw(
+                offset.shift_y(self.thickness),
+                offset.shift_xy(lid_len, self.thickness),
+                [("left", "left-inner"), ("right", "right-inner")],
+            ));
+        }
 
-        self.result
-            .append(side_wall.draw(offset.shift_y(self.thick_n(1)).origin(Origin::TopRight)));
+        self.result.append(
+            side_wall
+                .named("left")
+                .draw(offset.shift_y(self.thick_n(1)).origin(Origin::TopRight)),
+        );
 
         self.result.append(
             side_wall
                 .mirror_vertical()
+                .named("right")
                 .draw(offset.shift_xy(lid_len, self.thickness)),
         );
 
         offset.y += top_wall.square.h;
 
+        // Strip to glue the lid to the back wall of the box
+        if let LidType::Glued = self.ltype {
+            let strip = SquareElement::new(lid_len, self.height)
+                .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut)
+                .role(Role::GlueFlap);
+
+            self.result.append(
+                strip
+                    .named("back-strip")
+                    .hinged("top", Side::Top, 90.0)
+                    .draw(offset),
+            );
+
+            offset.y += strip.square.h;
+        }
+
         // Draw tail for separated lid
         if let LidType::Separated = self.ltype {
             // Flaps for side walls
-            let side_flap = side_flap.mirror_horisontal();
-            self.result.append(
-                side_flap.draw(
-                    offset
-                        .shift_nx(self.thickness)
-                        .shift_ny(self.thickness)
-                        .origin(Origin::TopRight),
-                ),
-            );
+            if let Some(side_flap) = side_flap.as_ref().map(|f| f.mirror_horisontal()) {
+                self.result.append(
+                    side_flap
+                        .named("left-back-flap")
+                        .hinged_side("left", Side::Top, 90.0)
+                        .draw(
+                            offset
+                                .shift_nx(self.thickness)
+                                .shift_ny(self.thickness)
+                                .origin(Origin::TopRight),
+                        ),
+                );
+
+                self.result.append(
+                    side_flap
+                        .mirror_vertical()
+                        .named("right-back-flap")
+                        .hinged_side("right", Side::Top, 90.0)
+                        .draw(
+                            offset
+                                .shift_xy(lid_len + self.thickness, -self.thickness)
+                                .origin(Origin::TopLeft),
+                        ),
+                );
+            }
+
+            let long_side = long_side.mirror_horisontal();
 
             self.result.append(
-                side_flap.mirror_vertical().draw(
-                    offset
-                        .shift_xy(lid_len + self.thickness, -1.0 * self.thickness)
-                        .origin(Origin::TopLeft),
-                ),
+                long_side
+                    .named("back")
+                    .hinged("top", Side::Top, 90.0)
+                    .draw(offset),
             );
 
-            let long_side = long_side.mirror_horisontal();
-
-            self.result.append(long_side.draw(offset));
+            self.result.net.glue("left-back-flap", "back");
+            self.result.net.glue("right-back-flap", "back");
 
             offset.y += long_side.square.h;
 
             let long_side_flap = long_side_flap.mirror_horisontal();
             let glue_flap_side_cut = glue_flap_side_cut.mirror_horisontal();
 
-            self.result
-                .append(long_side_flap.draw(offset.shift_x(self.glue_flap)));
+            self.result.append(
+                long_side_flap
+                    .named("back-flap")
+                    .hinged("back", Side::Top, 180.0)
+                    .draw(offset.shift_x(self.glue_flap)),
+            );
 
             self.result.append(glue_flap_side_cut.draw(offset));
 
             self.result
                 .append(glue_flap_side_cut.draw(offset.shift_x(lid_len).origin(Origin::TopRight)));
 
-            offset.y += if self.glue_flap > self.height * 2.0 {
+            offset.y += if self.glue_flap > self.height * Decimal::TWO {
                 self.glue_flap + self.thickness - self.height
             } else {
                 self.height
@@ -282,3 +591,129 @@ impl LidForBox {
         (offset, self.result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{dec, validate::check_fold};
+
+    fn lid(ltype: LidType) -> DrawResult {
+        lid_with(ltype, false)
+    }
+
+    fn lid_with(ltype: LidType, fat_border: bool) -> DrawResult {
+        LidForBox {
+            ltype,
+            height: dec(50, 0),
+            box_outer_width: dec(200, 0),
+            box_outer_length: dec(300, 0),
+            thickness: dec(2, 0),
+            glue_flap: dec(20, 0),
+            flap_style: FlapStyle::Square,
+            fat_border,
+            notch: None,
+            result: DrawResult::empty(FILE_NAME_DEFAULT.to_string()),
+        }
+        .draw()
+    }
+
+    const TYPES: [LidType; 3] = [LidType::Joined, LidType::Glued, LidType::Separated];
+
+    #[test]
+    fn width_grows_by_back_layers() {
+        let widths: Vec<Decimal> = TYPES
+            .iter()
+            .map(|t| {
+                let (min, max) = lid(*t).net.panel("top").unwrap().bounds();
+                max.y - min.y
+            })
+            .collect();
+        assert_eq!(widths, [dec(204, 0), dec(206, 0), dec(208, 0)]);
+    }
+
+    #[test]
+    fn every_type_is_cut_out_and_folds() {
+        for t in TYPES {
+            let res = lid(t);
+            assert!(res.outline_issues().is_empty(), "{t:?}");
+            assert_eq!(check_fold(&res.net).unwrap(), [], "{t:?}");
+        }
+    }
+
+    #[test]
+    fn back_of_the_lid() {
+        let joined = lid(LidType::Joined);
+        assert!(joined.net.panel("back").is_none());
+        assert!(joined.net.panel("back-strip").is_none());
+        let top = joined.net.panel("top").unwrap();
+        assert_eq!(top.edges[2], CutType::Cut);
+
+        let glued = lid(LidType::Glued);
+        assert!(glued.net.panel("back").is_none());
+        let strip = glued.net.panel("back-strip").unwrap();
+        assert_eq!(strip.hinge.as_ref().unwrap().parent, "top");
+
+        let separated = lid(LidType::Separated);
+        assert!(separated.net.panel("back").is_some());
+        assert!(separated.net.panel("back-strip").is_none());
+    }
+
+    #[test]
+    fn fat_walls_are_locked_in_the_top() {
+        for t in TYPES {
+            let res = lid_with(t, true);
+            assert!(res.outline_issues().is_empty(), "{t:?}");
+            assert_eq!(check_fold(&res.net).unwrap(), [], "{t:?}");
+
+            for name in ["left-inner", "right-inner"] {
+                let inner = res.net.panel(name).unwrap();
+                assert_eq!(inner.hinge.as_ref().unwrap().angle, 180.0);
+                // Layer with two tabs
+                assert_eq!(inner.vertices.len(), 12);
+            }
+            let slots = res
+                .net
+                .panels
+                .iter()
+                .filter(|p| p.role == Role::Hole)
+                .count();
+            assert_eq!(slots, 4, "{t:?}");
+        }
+    }
+
+    #[test]
+    fn fat_lid_preview_is_closed() {
+        use crate::common::fold::Vec3;
+        use crate::output::{render, OutputFormat};
+        use rust_decimal::prelude::ToPrimitive;
+
+        let res = lid_with(LidType::Separated, true);
+        let obj = String::from_utf8(render(OutputFormat::Obj, &res, 3).unwrap()).unwrap();
+        for name in ["top", "left-inner", "right-inner"] {
+            assert!(obj.lines().any(|l| l == format!("o {name}")), "{name}");
+        }
+
+        let stl = String::from_utf8(render(OutputFormat::Stl, &res, 3).unwrap()).unwrap();
+        let vertices: Vec<Vec3> = stl
+            .lines()
+            .filter_map(|l| l.strip_prefix("vertex "))
+            .map(|l| {
+                let v: Vec<f64> = l.split(' ').map(|n| n.parse().unwrap()).collect();
+                Vec3::new(v[0], v[1], v[2])
+            })
+            .collect();
+        // Solids with all faces looking outside have the volume of the boards
+        let volume: f64 = vertices
+            .chunks(3)
+            .map(|t| t[0].dot(t[1].cross(t[2])) / 6.0)
+            .sum();
+        let boards: f64 = res
+            .net
+            .panels
+            .iter()
+            .filter(|p| p.role.is_folded())
+            .map(|p| p.area() * res.net.thickness_of(p).to_f64().unwrap())
+            .sum();
+        assert!((volume - boards).abs() < boards * 1e-4, "{volume} {boards}");
+    }
+}
@@ -1,19 +1,28 @@
 mod box_cuboid;
+mod box_with_lid;
+mod check;
 mod common;
 pub mod lid;
+mod output;
+mod regen;
+mod serve;
+mod sweep;
 mod vinyl;
 
-use clap::Command;
+use clap::{ArgMatches, Command};
 use common::{args, DrawResult};
-use svg;
-use svg::Document;
 
-use crate::common::VIEWPORT_OFFSET;
-use anyhow::{bail, Result};
+use crate::output::OutputFormat;
+use anyhow::{anyhow, bail, Result};
 use clap::error::ErrorKind;
-use common::args::ArgsGlobal;
+use common::args::{ArgsGlobal, ModelParams};
+use common::report::{Report, ReportFormat};
 use env_logger::Builder;
 use log::LevelFilter;
+use std::io::Write;
+
+/// File name to write the drawing to stdout
+const STDOUT_FILE: &str = "-";
 
 fn main() {
     let mut builder = Builder::from_default_env();
@@ -29,11 +38,24 @@ fn main() {
     }
 }
 
+/// Subcommands which are box models
+pub const MODELS: [&str; 4] = [
+    box_cuboid::CLI_SUBCOMMAND,
+    box_with_lid::CLI_SUBCOMMAND,
+    vinyl::CLI_SUBCOMMAND,
+    lid::CLI_SUBCOMMAND,
+];
+
 fn cli_build() -> Command {
     let mut cmd = args::cli_base_args();
     cmd = box_cuboid::cli_build(cmd);
+    cmd = box_with_lid::cli_build(cmd);
     cmd = vinyl::cli_build(cmd);
     cmd = lid::cli_build(cmd);
+    cmd = serve::cli_build(cmd);
+    cmd = regen::cli_build(cmd);
+    cmd = check::cli_build(cmd);
+    cmd = sweep::cli_build(cmd);
     cmd
 }
 
@@ -63,61 +85,121 @@ fn execute() -> Result<()> {
     // }
 
     let draw_res = match matches.subcommand() {
-        Some((vinyl::CLI_SUBCOMMAND, subm)) => vinyl::cli_draw(subm),
-        Some((box_cuboid::CLI_SUBCOMMAND, subm)) => box_cuboid::cli_draw(subm),
-        Some((lid::CLI_SUBCOMMAND, subm)) => lid::cli_draw(subm),
+        Some((serve::CLI_SUBCOMMAND, subm)) => return serve::cli_run(subm),
+        Some((check::CLI_SUBCOMMAND, subm)) => return check::cli_run(subm),
+        Some((sweep::CLI_SUBCOMMAND, subm)) => return sweep::cli_run(subm, &globs),
+        Some((regen::CLI_SUBCOMMAND, subm)) => regen::cli_draw(subm),
+        Some((model, subm)) => draw_model(model, subm),
         _ => {
             log::error!("No subcommand. Should not execute here");
             std::process::exit(42);
         }
     }?;
 
-    write_svg(globs, draw_res)
+    write_output(globs, draw_res)
+}
+
+/// Draw model by the subcommand name
+pub fn draw_model(model: &str, m: &ArgMatches) -> Result<DrawResult> {
+    let mut drawing = match model {
+        vinyl::CLI_SUBCOMMAND => vinyl::cli_draw(m),
+        box_cuboid::CLI_SUBCOMMAND => box_cuboid::cli_draw(m),
+        box_with_lid::CLI_SUBCOMMAND => box_with_lid::cli_draw(m),
+        lid::CLI_SUBCOMMAND => lid::cli_draw(m),
+        _ => bail!("Неизвестная модель: {model}"),
+    }?;
+
+    let cli = cli_build();
+    drawing.params = cli
+        .find_subcommand(model)
+        .map(|cmd| ModelParams::from_matches(cmd, m));
+
+    Ok(drawing)
 }
 
-fn write_svg(args: ArgsGlobal, drawing: DrawResult) -> Result<()> {
-    log::trace!("DRAW PATHS: \n{:?}", drawing.paths);
+/// Draw model with parameters which are not from the command line
+pub fn draw_params(params: &ModelParams) -> Result<DrawResult> {
+    let cli = cli_build();
+    let argv = params.to_argv(&cli)?;
+    let matches = cli
+        .try_get_matches_from(argv)
+        .map_err(|e| anyhow!(e.render().to_string()))?;
+
+    match matches.subcommand() {
+        Some((model, subm)) => draw_model(model, subm),
+        None => bail!("Не указана модель"),
+    }
+}
 
-    let max = drawing.max.shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
-    let mut document = Document::new()
-        .set("width", format!("{}mm", max.x))
-        .set("height", format!("{}mm", max.y))
-        .set("viewBox", (0, 0, max.x, max.y));
+fn write_output(args: ArgsGlobal, drawing: DrawResult) -> Result<()> {
+    log::trace!("DRAW PATHS: \n{:?}", drawing.segments());
+
+    for issue in drawing.outline_issues() {
+        log::warn!("{issue}");
+    }
 
+    let max = output::sheet_size(&drawing);
     log::info!(
         "Размеры листа:\n - Ширина:{}мм\n - Высота:{}мм ",
         max.x,
         max.y
     );
 
-    for p in drawing.paths {
-        document = document.add(p);
-    }
-
-    let save_path = match args.file {
+    let save_path = match &args.file {
         None => {
             log::info!(
                 "Используется имя файла по умолчанию {}",
                 drawing.default_file_name
             );
-            drawing.default_file_name
+            drawing.default_file_name.clone()
         }
         Some(f) => {
-            if f.to_uppercase().ends_with(".SVG") {
-                f
-            } else {
-                bail!("ДА щаз! Имя файла должно заканчиваться на .svg а ты что ввел?");
-            }
+            let params = drawing.params.clone().unwrap_or_default();
+            params.fill(&cli_build(), f, &args.file_vars())?
         }
     };
 
-    if std::path::Path::new(&save_path).exists() {
-        log::debug!("Существующий файл будет перезаписан");
+    args.check_machine()?;
+    if args.report == Some(ReportFormat::Json) && save_path == STDOUT_FILE {
+        bail!("Отчет json и чертеж не поместятся в консоль вместе, укажите файл в -f");
+    }
+    let manifest = std::path::Path::new(&save_path).with_extension(OutputFormat::Json.extension());
+    if args.manifest && (save_path == STDOUT_FILE || manifest.to_str() == Some(save_path.as_str()))
+    {
+        bail!("Для --manifest нужен файл чертежа в -f, список деталей запишется рядом в .json");
+    }
+    write_file(&save_path, &drawing, args.precision)?;
+
+    if args.manifest {
+        write_file(manifest.to_str().unwrap(), &drawing, args.precision)?;
+    }
+
+    if let Some(format) = args.report {
+        let report = Report::new(&drawing, &args.machine);
+        match format {
+            ReportFormat::Text => log::info!("{report}"),
+            ReportFormat::Json => println!("{:#}", report.to_json()),
+        }
     }
+    Ok(())
+}
 
-    let res = svg::save(&save_path, &document).map_err(anyhow::Error::from);
-    if res.is_ok() {
-        log::info!("Файл записан: {}", save_path);
+/// Render the drawing in the format given by the file extension, SVG to stdout for `-`
+pub fn write_file(save_path: &str, drawing: &DrawResult, precision: u32) -> Result<()> {
+    if save_path == STDOUT_FILE {
+        std::io::stdout().write_all(&output::render(OutputFormat::Svg, drawing, precision)?)?;
+        return Ok(());
     }
-    res
+
+    let Ok(format) = OutputFormat::from_path(save_path) else {
+        bail!("ДА щаз! Имя файла должно заканчиваться на .svg, .dxf, .pdf, .obj, .stl или .json а ты что ввел?");
+    };
+
+    if std::path::Path::new(save_path).exists() {
+        log::debug!("Существующий файл будет перезаписан");
+    }
+
+    std::fs::write(save_path, output::render(format, drawing, precision)?)?;
+    log::info!("Файл записан: {}", save_path);
+    Ok(())
 }
@@ -0,0 +1,121 @@
+//! Minimal ASCII DXF (R12) writer.
+//! Units are millimeters, Y axis points up so the drawing is flipped.
+use std::fmt::Write;
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use super::{num, sheet_size};
+use crate::common::{CutType, DrawResult, Segment};
+
+fn layer(tp: &CutType) -> (&'static str, u8) {
+    match tp {
+        CutType::Nope => ("NOPE", 8),
+        CutType::Cut => ("CUT", 7),
+        CutType::Bend => ("BEND", 3),
+    }
+}
+
+fn pair(out: &mut String, code: u16, value: impl std::fmt::Display) {
+    let _ = write!(out, "{code}\n{value}\n");
+}
+
+fn dec_f64(v: f64) -> Decimal {
+    Decimal::from_f64(v).unwrap_or_default()
+}
+
+fn float(v: f64, precision: u32) -> String {
+    num(dec_f64(v), precision)
+}
+
+pub fn render(drawing: &DrawResult, precision: u32) -> String {
+    let max = sheet_size(drawing);
+    let mut out = String::new();
+
+    pair(&mut out, 0, "SECTION");
+    pair(&mut out, 2, "HEADER");
+    pair(&mut out, 9, "$INSUNITS");
+    pair(&mut out, 70, 4);
+    pair(&mut out, 9, "$EXTMIN");
+    pair(&mut out, 10, 0.0);
+    pair(&mut out, 20, 0.0);
+    pair(&mut out, 9, "$EXTMAX");
+    pair(&mut out, 10, num(max.x, precision));
+    pair(&mut out, 20, num(max.y, precision));
+    pair(&mut out, 0, "ENDSEC");
+
+    pair(&mut out, 0, "SECTION");
+    pair(&mut out, 2, "TABLES");
+    pair(&mut out, 0, "TABLE");
+    pair(&mut out, 2, "LAYER");
+    for tp in [CutType::Cut, CutType::Bend] {
+        let (name, color) = layer(&tp);
+        pair(&mut out, 0, "LAYER");
+        pair(&mut out, 2, name);
+        pair(&mut out, 70, 0);
+        pair(&mut out, 62, color);
+        pair(&mut out, 6, "CONTINUOUS");
+    }
+    pair(&mut out, 0, "ENDTAB");
+    pair(&mut out, 0, "ENDSEC");
+
+    pair(&mut out, 0, "SECTION");
+    pair(&mut out, 2, "ENTITIES");
+    for segment in drawing.segments().iter() {
+        let (name, color) = layer(&segment.tp());
+        match segment {
+            Segment::Line(line) => {
+                pair(&mut out, 0, "LINE");
+                pair(&mut out, 8, name);
+                pair(&mut out, 62, color);
+                pair(&mut out, 10, num(line.from.x, precision));
+                pair(&mut out, 20, num(max.y - line.from.y, precision));
+                pair(&mut out, 11, num(line.to.x, precision));
+                pair(&mut out, 21, num(max.y - line.to.y, precision));
+            }
+            Segment::Arc(arc) => {
+                pair(&mut out, 0, if arc.is_circle() { "CIRCLE" } else { "ARC" });
+                pair(&mut out, 8, name);
+                pair(&mut out, 62, color);
+                pair(&mut out, 10, num(arc.center.x, precision));
+                pair(&mut out, 20, num(max.y - arc.center.y, precision));
+                pair(&mut out, 40, float(arc.radius(), precision));
+                if !arc.is_circle() {
+                    // Flipped Y turns sheet angles over, DXF arcs always go counterclockwise
+                    let (start, sweep) = arc.angles();
+                    let (from, to) = if sweep > 0.0 {
+                        (-(start + sweep), -start)
+                    } else {
+                        (-start, -(start + sweep))
+                    };
+                    pair(
+                        &mut out,
+                        50,
+                        float(from.to_degrees().rem_euclid(360.0), precision),
+                    );
+                    pair(
+                        &mut out,
+                        51,
+                        float(to.to_degrees().rem_euclid(360.0), precision),
+                    );
+                }
+            }
+            // R12 has no splines
+            Segment::Cubic(_) => {
+                for w in segment.flatten().windows(2) {
+                    pair(&mut out, 0, "LINE");
+                    pair(&mut out, 8, name);
+                    pair(&mut out, 62, color);
+                    pair(&mut out, 10, float(w[0].0, precision));
+                    pair(&mut out, 20, num(max.y - dec_f64(w[0].1), precision));
+                    pair(&mut out, 11, float(w[1].0, precision));
+                    pair(&mut out, 21, num(max.y - dec_f64(w[1].1), precision));
+                }
+            }
+        }
+    }
+    pair(&mut out, 0, "ENDSEC");
+    pair(&mut out, 0, "EOF");
+
+    out
+}
@@ -0,0 +1,82 @@
+//! Machine readable description of the drawing: panels with roles, sizes and positions on the sheet.
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value};
+
+use super::sheet_size;
+use crate::common::{DrawResult, Panel, Role};
+
+/// Decimal rounded to `precision` places as JSON number
+fn number(v: Decimal, precision: u32) -> Value {
+    json!(v
+        .round_dp(precision)
+        .normalize()
+        .to_f64()
+        .unwrap_or_default())
+}
+
+fn area(p: &Panel, precision: u32) -> Value {
+    number(Decimal::from_f64(p.area()).unwrap_or_default(), precision)
+}
+
+fn panel(drawing: &DrawResult, p: &Panel, precision: u32) -> Value {
+    let (min, max) = p.bounds();
+    json!({
+        "name": p.name,
+        "role": p.role.name(),
+        "x": number(min.x, precision),
+        "y": number(min.y, precision),
+        "width": number(max.x - min.x, precision),
+        "height": number(max.y - min.y, precision),
+        "area": area(p, precision),
+        "thickness": number(drawing.net.thickness_of(p), precision),
+        "hinge": p.hinge.as_ref().map(|h| json!({ "parent": h.parent, "angle": h.angle })),
+    })
+}
+
+pub fn render(drawing: &DrawResult, precision: u32) -> String {
+    let net = &drawing.net;
+    let sheet = sheet_size(drawing);
+
+    let params: Map<String, Value> = drawing
+        .params
+        .iter()
+        .flat_map(|p| p.values.iter())
+        .map(|(n, v)| (n.clone(), json!(v)))
+        .collect();
+
+    let glue: Vec<Value> = net
+        .glue
+        .iter()
+        .map(|g| {
+            json!({
+                "flap": g.flap,
+                "target": g.target,
+                "area": net.panel(&g.flap).map(|p| area(p, precision)),
+            })
+        })
+        .collect();
+    // Area of the flaps covered with glue, even not paired ones
+    let glue_area: f64 = net
+        .panels
+        .iter()
+        .filter(|p| p.role == Role::GlueFlap)
+        .map(|p| p.area())
+        .sum();
+
+    let manifest = json!({
+        "boxcut": env!("CARGO_PKG_VERSION"),
+        "model": drawing.params.as_ref().map(|p| p.model.clone()),
+        "params": params,
+        "thickness": number(net.thickness, precision),
+        "sheet": {
+            "width": number(sheet.x, precision),
+            "height": number(sheet.y, precision),
+        },
+        "panels": net.panels.iter().map(|p| panel(drawing, p, precision)).collect::<Vec<_>>(),
+        "glue": glue,
+        "glue_area": number(Decimal::from_f64(glue_area).unwrap_or_default(), precision),
+    });
+
+    format!("{manifest:#}\n")
+}
@@ -0,0 +1,246 @@
+//! Folded box as 3D mesh (OBJ, STL).
+//! Panels are extruded by the board thickness to the outside of the box.
+//! Exported coordinates are millimeters with Y axis flipped to get a right handed system.
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::common::fold::{fold, Vec3};
+use crate::common::validate::check_fold;
+use crate::common::DrawResult;
+
+/// Points of a face closer than this (mm, mm² for turns) are the same
+const EPSILON: f64 = 1e-9;
+
+/// Named panel solids, each is a list of faces
+fn solids(drawing: &DrawResult) -> Result<Vec<(String, Vec<Vec<Vec3>>)>> {
+    let folded: Vec<_> = fold(&drawing.net)?
+        .into_iter()
+        .filter(|f| f.panel.role.is_folded())
+        .collect();
+
+    if folded.is_empty() {
+        bail!("Модель не описывает свои панели, 3D вид недоступен");
+    }
+
+    for issue in check_fold(&drawing.net)? {
+        log::warn!("{issue}");
+    }
+
+    let mut res = Vec::new();
+
+    for f in folded {
+        let thickness = drawing
+            .net
+            .thickness_of(f.panel)
+            .to_f64()
+            .unwrap_or_default();
+        let top: Vec<Vec3> = f.vertices().into_iter().map(export).collect();
+        let shift = f.normal() * -thickness;
+        let bottom: Vec<Vec3> = f
+            .vertices()
+            .into_iter()
+            .map(|v| export(v + shift))
+            .collect();
+
+        // Faces look outside of the solid: the top and the bottom away from each other,
+        // the sides away from the panel whatever its outline is
+        let ring = face_normal(&top);
+        let mut faces = vec![
+            outwards(top.clone(), export(shift) * -1.0),
+            outwards(bottom.clone(), export(shift)),
+        ];
+        let n = top.len();
+        for i in 0..n {
+            let j = (i + 1) % n;
+            let side = vec![top[i], top[j], bottom[j], bottom[i]];
+            faces.push(outwards(side, (top[j] - top[i]).cross(ring)));
+        }
+
+        res.push((f.panel.name.clone().unwrap_or_default(), faces));
+    }
+
+    Ok(res)
+}
+
+fn export(v: Vec3) -> Vec3 {
+    Vec3::new(v.x, -v.y, v.z)
+}
+
+/// Face with the order of vertices turned to look along `outside`
+fn outwards(mut face: Vec<Vec3>, outside: Vec3) -> Vec<Vec3> {
+    if face_normal(&face).dot(outside) < 0.0 {
+        face.reverse();
+    }
+    face
+}
+
+/// Normal of the flat face by Newell's method, the face is counterclockwise around it.
+/// Works for concave faces too, unlike the cross product of two edges.
+fn face_normal(face: &[Vec3]) -> Vec3 {
+    let mut n = Vec3::new(0.0, 0.0, 0.0);
+    for (i, a) in face.iter().enumerate() {
+        let b = face[(i + 1) % face.len()];
+        n = n + Vec3::new(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        );
+    }
+    n.normalize()
+}
+
+/// Triangles of the flat face as vertex indices, ear clipping keeps concave faces right
+fn triangulate(face: &[Vec3]) -> Vec<[usize; 3]> {
+    // Coordinates in the plane of the face, counterclockwise as the face around its normal
+    let n = face_normal(face);
+    let axis = if n.x.abs() < 0.9 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    let u = n.cross(axis).normalize();
+    let v = n.cross(u);
+    let pts: Vec<(f64, f64)> = face.iter().map(|p| (p.dot(u), p.dot(v))).collect();
+
+    let turn = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (pts[a], pts[b], pts[c]);
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+    };
+    let same = |a: usize, b: usize| {
+        (pts[a].0 - pts[b].0).abs() <= EPSILON && (pts[a].1 - pts[b].1).abs() <= EPSILON
+    };
+
+    let mut left: Vec<usize> = (0..face.len()).collect();
+    let mut res = Vec::new();
+    while left.len() > 3 {
+        let m = left.len();
+        let corner = |i: usize| (left[(i + m - 1) % m], left[i], left[(i + 1) % m]);
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = corner(i);
+            turn(a, b, c) > EPSILON
+                && left.iter().all(|&k| {
+                    same(k, a)
+                        || same(k, b)
+                        || same(k, c)
+                        || turn(a, b, k) < -EPSILON
+                        || turn(b, c, k) < -EPSILON
+                        || turn(c, a, k) < -EPSILON
+                })
+        });
+        // Only collinear points are left, any triangle has no area
+        let i = ear.unwrap_or(0);
+        let (a, b, c) = corner(i);
+        res.push([a, b, c]);
+        left.remove(i);
+    }
+    res.push([left[0], left[1], left[2]]);
+
+    res
+}
+
+pub fn render_obj(drawing: &DrawResult) -> Result<String> {
+    let mut out = String::from("# boxcut folded preview, mm\n");
+    let mut index = 1;
+
+    for (name, faces) in solids(drawing)? {
+        let _ = writeln!(out, "o {name}");
+        for face in faces.iter() {
+            let mut f = String::from("f");
+            for v in face.iter() {
+                let _ = writeln!(out, "v {:.4} {:.4} {:.4}", v.x, v.y, v.z);
+                let _ = write!(f, " {index}");
+                index += 1;
+            }
+            let _ = writeln!(out, "{f}");
+        }
+    }
+
+    Ok(out)
+}
+
+pub fn render_stl(drawing: &DrawResult) -> Result<String> {
+    let mut out = String::from("solid boxcut\n");
+
+    for (_, faces) in solids(drawing)? {
+        for face in faces.iter() {
+            let n = face_normal(face);
+            for tri in triangulate(face) {
+                let tri = tri.map(|i| face[i]);
+                let _ = writeln!(out, "facet normal {:.4} {:.4} {:.4}", n.x, n.y, n.z);
+                out.push_str("outer loop\n");
+                for v in tri {
+                    let _ = writeln!(out, "vertex {:.4} {:.4} {:.4}", v.x, v.y, v.z);
+                }
+                out.push_str("endloop\nendfacet\n");
+            }
+        }
+    }
+
+    out.push_str("endsolid boxcut\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{dec, CutType, Point, PolygonElement, Role};
+
+    /// Triangles of STL with their normals
+    fn facets(stl: &str) -> Vec<(Vec3, [Vec3; 3])> {
+        let numbers = |line: &str| -> Vec3 {
+            let v: Vec<f64> = line
+                .split_whitespace()
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            Vec3::new(v[0], v[1], v[2])
+        };
+        let lines: Vec<&str> = stl.lines().collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.starts_with("facet normal"))
+            .map(|(i, l)| {
+                let v = [2, 3, 4].map(|k| numbers(lines[i + k]));
+                (numbers(l), v)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn concave_panel_is_closed_solid() {
+        // U shape 30x20 with 10x10 slot, fan from any corner goes out of the outline
+        let pt = |x, y| Point::new(dec(x, 0), dec(y, 0));
+        let u = PolygonElement::new(
+            &[
+                pt(0, 0),
+                pt(10, 0),
+                pt(10, 10),
+                pt(20, 10),
+                pt(20, 0),
+                pt(30, 0),
+                pt(30, 20),
+                pt(0, 20),
+            ],
+            &[CutType::Cut; 8],
+        )
+        .unwrap()
+        .role(Role::Bottom)
+        .named("bottom");
+        let mut drawing = u.draw(Point::zero());
+        drawing.net.thickness = dec(2, 0);
+
+        let (mut area, mut volume) = (0.0, 0.0);
+        for (n, [a, b, c]) in facets(&render_stl(&drawing).unwrap()) {
+            let cross = (b - a).cross(c - a);
+            assert!(cross.len() < EPSILON || cross.dot(n) > 0.0);
+            area += cross.len() / 2.0;
+            volume += a.dot(b.cross(c)) / 6.0;
+        }
+        // Top and bottom of 500mm², sides along 120mm of outline
+        assert!((area - (2.0 * 500.0 + 120.0 * 2.0)).abs() < 1e-3, "{area}");
+        // Positive when all faces look outside
+        assert!((volume - 1000.0).abs() < 1e-3, "{volume}");
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+
+use crate::common::{dec, CutType, DrawResult, Point, VIEWPORT_OFFSET};
+
+mod dxf;
+mod manifest;
+mod mesh;
+mod pdf;
+mod svg;
+
+pub use self::svg::{read_params, read_segments};
+
+/// Supported output file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Svg,
+    Dxf,
+    Pdf,
+    /// Folded 3D preview
+    Obj,
+    /// Folded 3D preview
+    Stl,
+    /// Panels of the drawing for other programs
+    Json,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 6] = [
+        OutputFormat::Svg,
+        OutputFormat::Dxf,
+        OutputFormat::Pdf,
+        OutputFormat::Obj,
+        OutputFormat::Stl,
+        OutputFormat::Json,
+    ];
+
+    /// Format by name or file extension (case insensitive)
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "svg" => Ok(Self::Svg),
+            "dxf" => Ok(Self::Dxf),
+            "pdf" => Ok(Self::Pdf),
+            "obj" => Ok(Self::Obj),
+            "stl" => Ok(Self::Stl),
+            "json" => Ok(Self::Json),
+            _ => bail!("Неизвестный формат {name}. Поддерживаются: svg, dxf, pdf, obj, stl, json"),
+        }
+    }
+
+    /// Format by file extension of the path
+    pub fn from_path(path: &str) -> Result<Self> {
+        match std::path::Path::new(path).extension() {
+            Some(ext) => Self::from_name(&ext.to_string_lossy()),
+            None => bail!("У файла {path} нет расширения (.svg, .dxf, .pdf, .obj, .stl или .json)"),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Dxf => "dxf",
+            Self::Pdf => "pdf",
+            Self::Obj => "obj",
+            Self::Stl => "stl",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Svg => "image/svg+xml",
+            Self::Dxf => "image/vnd.dxf",
+            Self::Pdf => "application/pdf",
+            Self::Obj => "model/obj",
+            Self::Stl => "model/stl",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Sheet size including viewport margins
+pub fn sheet_size(drawing: &DrawResult) -> Point {
+    drawing.max.shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET)
+}
+
+/// Decimal places of coordinates when not configured
+pub const DEFAULT_PRECISION: u32 = 3;
+
+/// Coordinate rounded to `precision` decimal places without trailing zeros
+fn num(v: Decimal, precision: u32) -> String {
+    let v = v.round_dp(precision).normalize();
+    if v.is_zero() {
+        "0".into()
+    } else {
+        v.to_string()
+    }
+}
+
+/// Render drawing into the file content of requested format,
+/// coordinates are rounded to `precision` decimal places
+pub fn render(format: OutputFormat, drawing: &DrawResult, precision: u32) -> Result<Vec<u8>> {
+    Ok(match format {
+        OutputFormat::Svg => svg::render(drawing, precision).into_bytes(),
+        OutputFormat::Dxf => dxf::render(drawing, precision).into_bytes(),
+        OutputFormat::Pdf => pdf::render(drawing, precision),
+        OutputFormat::Obj => mesh::render_obj(drawing)?.into_bytes(),
+        OutputFormat::Stl => mesh::render_stl(drawing)?.into_bytes(),
+        OutputFormat::Json => manifest::render(drawing, precision).into_bytes(),
+    })
+}
+
+/// RGB color of the stroke for the cut type
+fn stroke_rgb(tp: &CutType) -> (u8, u8, u8) {
+    match tp {
+        CutType::Nope => (255, 255, 255),
+        CutType::Cut => (0, 0, 0),
+        CutType::Bend => (0, 128, 0),
+    }
+}
+
+/// Width of the stroke (mm)
+const STROKE_WIDTH: Decimal = dec(2, 1);
+
+pub fn xml_escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn xml_unescape(input: &str) -> String {
+    input
+        .replace("&quot;", "\"")
+        .replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,114 @@
+//! Minimal single page PDF writer.
+//! Page size matches the sheet, PDF Y axis points up so the drawing is flipped.
+use std::fmt::Write;
+
+use rust_decimal::Decimal;
+
+use rust_decimal::prelude::FromPrimitive;
+
+use super::{num, sheet_size, stroke_rgb, STROKE_WIDTH};
+use crate::common::{dec, Arc, DrawResult, Segment};
+
+/// PDF has no arcs, they are drawn with Bezier curves of up to a quarter circle each.
+/// Returns both control points and the end of every curve.
+fn arc_curves(arc: &Arc) -> Vec<[(f64, f64); 3]> {
+    let (cx, cy) = arc.center.to_f64();
+    let r = arc.radius();
+    let (start, sweep) = arc.angles();
+    let n = (sweep.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep / n as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+
+    let at = |a: f64| (cx + r * a.cos(), cy + r * a.sin());
+    (0..n)
+        .map(|i| {
+            let (a0, a1) = (start + step * i as f64, start + step * (i + 1) as f64);
+            let (p0, p1) = (at(a0), at(a1));
+            [
+                (p0.0 - k * r * a0.sin(), p0.1 + k * r * a0.cos()),
+                (p1.0 + k * r * a1.sin(), p1.1 - k * r * a1.cos()),
+                p1,
+            ]
+        })
+        .collect()
+}
+
+pub fn render(drawing: &DrawResult, precision: u32) -> Vec<u8> {
+    let max = sheet_size(drawing);
+    // PDF points in one millimeter
+    let pt_per_mm = Decimal::from(72) / dec(254, 1);
+    let pt = |mm: Decimal| num(mm * pt_per_mm, precision);
+
+    let mut content = String::new();
+    let _ = writeln!(content, "{} w 1 J 1 j", pt(STROKE_WIDTH));
+    let xy = |x: Decimal, y: Decimal| format!("{} {}", pt(x), pt(max.y - y));
+    let xyf = |(x, y): (f64, f64)| {
+        xy(
+            Decimal::from_f64(x).unwrap_or_default(),
+            Decimal::from_f64(y).unwrap_or_default(),
+        )
+    };
+    for segment in drawing.segments().iter() {
+        let (r, g, b) = stroke_rgb(&segment.tp());
+        let from = segment.from();
+        let path = match segment {
+            Segment::Line(l) => format!("{} l", xy(l.to.x, l.to.y)),
+            Segment::Cubic(c) => format!(
+                "{} {} {} c",
+                xy(c.c1.x, c.c1.y),
+                xy(c.c2.x, c.c2.y),
+                xy(c.to.x, c.to.y)
+            ),
+            Segment::Arc(a) => arc_curves(a)
+                .iter()
+                .map(|[c1, c2, to]| format!("{} {} {} c", xyf(*c1), xyf(*c2), xyf(*to)))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        let _ = writeln!(
+            content,
+            "{:.3} {:.3} {:.3} RG {} m {} S",
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0,
+            xy(from.x, from.y),
+            path,
+        );
+    }
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R >>",
+            pt(max.x),
+            pt(max.y)
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, obj) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{}\nendobj\n", i + 1, obj);
+    }
+
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for off in offsets {
+        let _ = writeln!(out, "{off:010} 00000 n ");
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+
+    out.into_bytes()
+}
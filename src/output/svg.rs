@@ -0,0 +1,435 @@
+use anyhow::{bail, Result};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use svg::node::element::path::{Command, Data, Position};
+use svg::node::element::tag::Type;
+use svg::node::element::{Description, Element, Path};
+use svg::node::{Attributes, Node, Text};
+use svg::parser::Event;
+use svg::Document;
+
+use super::{num, sheet_size, xml_escape, xml_unescape, STROKE_WIDTH};
+use crate::common::args::ModelParams;
+use crate::common::{
+    draw_arc, draw_circle, draw_cubic, draw_line, CutType, DrawResult, Point, Segment,
+    FLATTEN_STEPS,
+};
+
+/// Namespace of the generation parameters in `<metadata>`
+const META_NS: &str = "https://github.com/rustrum/boxcut";
+const META_MODEL: &str = "boxcut:model";
+const META_PARAM: &str = "boxcut:param";
+
+fn stroke(tp: &CutType) -> &'static str {
+    match tp {
+        CutType::Nope => "white",
+        CutType::Cut => "black",
+        CutType::Bend => "green",
+    }
+}
+
+fn path_for(segment: &Segment, precision: u32) -> Path {
+    let xy = |p: Point| format!("{},{}", num(p.x, precision), num(p.y, precision));
+
+    let data = match segment {
+        Segment::Line(l) => format!("M{} L{}", xy(l.from), xy(l.to)),
+        Segment::Arc(a) => {
+            let r = num(Decimal::from_f64(a.radius()).unwrap_or_default(), precision);
+            let sweep = u8::from(a.clockwise);
+            if a.is_circle() {
+                // Single arc can't end where it starts, draw two halves
+                let opposite = Point::new(
+                    a.center.x * Decimal::TWO - a.from.x,
+                    a.center.y * Decimal::TWO - a.from.y,
+                );
+                format!(
+                    "M{} A{r},{r} 0 0,{sweep} {} A{r},{r} 0 0,{sweep} {}",
+                    xy(a.from),
+                    xy(opposite),
+                    xy(a.from)
+                )
+            } else {
+                let large = u8::from(a.angles().1.abs() > std::f64::consts::PI);
+                format!("M{} A{r},{r} 0 {large},{sweep} {}", xy(a.from), xy(a.to))
+            }
+        }
+        Segment::Cubic(c) => format!("M{} C{} {} {}", xy(c.from), xy(c.c1), xy(c.c2), xy(c.to)),
+    };
+
+    Path::new()
+        .set("fill", "none")
+        .set("stroke", stroke(&segment.tp()))
+        .set("stroke-width", STROKE_WIDTH.to_string())
+        .set("d", data)
+}
+
+/// Model name, parameters and boxcut version
+fn metadata(params: &ModelParams) -> (Description, Element) {
+    let version = env!("CARGO_PKG_VERSION");
+
+    let mut desc = format!("boxcut {version}: {}", params.model);
+    let mut model = Element::new(META_MODEL);
+    model.assign("xmlns:boxcut", META_NS);
+    model.assign("name", xml_escape(&params.model));
+    model.assign("version", version);
+
+    for (name, value) in params.values.iter() {
+        desc.push_str(&format!(" {name}={value}"));
+
+        let mut param = Element::new(META_PARAM);
+        param.assign("name", xml_escape(name));
+        param.assign("value", xml_escape(value));
+        model.append(param);
+    }
+
+    let mut meta = Element::new("metadata");
+    meta.append(model);
+
+    (Description::new().add(Text::new(xml_escape(&desc))), meta)
+}
+
+pub fn document(drawing: &DrawResult, precision: u32) -> Document {
+    let max = sheet_size(drawing);
+    let (w, h) = (num(max.x, precision), num(max.y, precision));
+    let mut document = Document::new()
+        .set("width", format!("{w}mm"))
+        .set("height", format!("{h}mm"))
+        .set("viewBox", format!("0 0 {w} {h}"));
+
+    if let Some(params) = drawing.params.as_ref() {
+        let (desc, meta) = metadata(params);
+        document = document.add(desc).add(meta);
+    }
+
+    for segment in drawing.segments().iter() {
+        document = document.add(path_for(segment, precision));
+    }
+
+    document
+}
+
+pub fn render(drawing: &DrawResult, precision: u32) -> String {
+    document(drawing, precision).to_string()
+}
+
+/// Read generation parameters and boxcut version from SVG content
+pub fn read_params(content: &str) -> Result<(ModelParams, String)> {
+    let mut params = None;
+    let mut version = String::new();
+
+    for event in svg::read(content)? {
+        match event {
+            Event::Tag(_, Type::End, _) => {}
+            Event::Tag(META_MODEL, _, attrs) => {
+                let model = attrs.get("name").map(|v| xml_unescape(v));
+                params = Some(ModelParams::new(&model.unwrap_or_default()));
+                version = attrs
+                    .get("version")
+                    .map(|v| v.to_string())
+                    .unwrap_or_default();
+            }
+            Event::Tag(META_PARAM, _, attrs) => {
+                let (Some(p), Some(name), Some(value)) =
+                    (params.as_mut(), attrs.get("name"), attrs.get("value"))
+                else {
+                    continue;
+                };
+                p.set(&xml_unescape(name), &xml_unescape(value));
+            }
+            Event::Error(e) => bail!("Не удалось прочитать SVG: {e}"),
+            _ => {}
+        }
+    }
+
+    match params {
+        Some(p) => Ok((p, version)),
+        None => bail!("В файле нет параметров boxcut, файл создан не boxcut или старой версией"),
+    }
+}
+
+/// Point of the SVG being read, coordinates there are not exact anyway
+#[derive(Debug, Clone, Copy)]
+struct Xy {
+    x: f64,
+    y: f64,
+}
+
+impl Xy {
+    fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    fn shift_xy(&self, dx: f64, dy: f64) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+
+    fn shift_x(&self, dx: f64) -> Self {
+        self.shift_xy(dx, 0.0)
+    }
+
+    fn shift_y(&self, dy: f64) -> Self {
+        self.shift_xy(0.0, dy)
+    }
+
+    fn point(&self) -> Point {
+        Point::from_f64(self.x, self.y)
+    }
+}
+
+/// Cut type by the stroke color, boxcut draws bends green
+fn cut_type(attrs: &Attributes) -> CutType {
+    let style_stroke = attrs.get("style").and_then(|s| {
+        s.split(';')
+            .filter_map(|kv| kv.split_once(':'))
+            .find(|(k, _)| k.trim() == "stroke")
+            .map(|(_, v)| v.trim().to_lowercase())
+    });
+    let stroke = style_stroke
+        .or_else(|| attrs.get("stroke").map(|v| v.trim().to_lowercase()))
+        .unwrap_or_default();
+
+    match stroke.as_str() {
+        "green" | "lime" | "#0f0" | "#00ff00" | "#008000" => CutType::Bend,
+        "white" | "#fff" | "#ffffff" => CutType::Nope,
+        _ => CutType::Cut,
+    }
+}
+
+fn attr_f64(attrs: &Attributes, name: &str) -> f64 {
+    attrs
+        .get(name)
+        .and_then(|v| v.trim().trim_end_matches("px").parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Xys of `points` attribute of polyline and polygon
+fn parse_points(value: &str) -> Vec<Xy> {
+    let nums: Vec<f64> = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    nums.chunks_exact(2).map(|c| Xy::new(c[0], c[1])).collect()
+}
+
+/// Segments of the arc after `from`, see SVG implementation notes F.6.5.
+/// Circular arcs are kept as they are, elliptical ones are split into straight pieces.
+fn arc(from: Xy, p: &[f64], to: Xy, tp: &CutType) -> Vec<Segment> {
+    let (mut rx, mut ry) = (p[0].abs(), p[1].abs());
+    if rx == 0.0 || ry == 0.0 {
+        return draw_line(from.point(), to.point(), tp)
+            .map(Segment::Line)
+            .into_iter()
+            .collect();
+    }
+    if from.x == to.x && from.y == to.y {
+        return Vec::new();
+    }
+    let (sin, cos) = p[2].to_radians().sin_cos();
+    let (large, sweep) = (p[3] != 0.0, p[4] != 0.0);
+
+    let (dx, dy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    let scale = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if scale > 1.0 {
+        rx *= scale.sqrt();
+        ry *= scale.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut k = (num / den).max(0.0).sqrt();
+    if large == sweep {
+        k = -k;
+    }
+    let (cx1, cy1) = (k * rx * y1 / ry, -k * ry * x1 / rx);
+    let cx = cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0;
+
+    if (rx - ry).abs() < 1e-9 {
+        let center = Xy::new(cx, cy);
+        return draw_arc(from.point(), to.point(), center.point(), sweep, tp)
+            .map(Segment::Arc)
+            .into_iter()
+            .collect();
+    }
+
+    let angle = |ux: f64, uy: f64| uy.atan2(ux);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - start;
+    let full = std::f64::consts::TAU;
+    if sweep && delta < 0.0 {
+        delta += full;
+    } else if !sweep && delta > 0.0 {
+        delta -= full;
+    }
+
+    let mut points = vec![from];
+    points.extend((1..=FLATTEN_STEPS).map(|i| {
+        if i == FLATTEN_STEPS {
+            return to;
+        }
+        let (s, c) = (start + delta * i as f64 / FLATTEN_STEPS as f64).sin_cos();
+        Xy::new(
+            cx + cos * rx * c - sin * ry * s,
+            cy + sin * rx * c + cos * ry * s,
+        )
+    }));
+    polyline(&points, tp)
+}
+
+/// Straight segments between the points
+fn polyline(points: &[Xy], tp: &CutType) -> Vec<Segment> {
+    points
+        .windows(2)
+        .filter_map(|w| draw_line(w[0].point(), w[1].point(), tp))
+        .map(Segment::Line)
+        .collect()
+}
+
+fn cubic(points: [Xy; 4], tp: &CutType) -> Vec<Segment> {
+    let [from, c1, c2, to] = points.map(|p| p.point());
+    draw_cubic(from, c1, c2, to, tp)
+        .map(Segment::Cubic)
+        .into_iter()
+        .collect()
+}
+
+/// Quadratic curve is the cubic one with control points 2/3 of the way to the quadratic control point
+fn quadratic(from: Xy, q: Xy, to: Xy, tp: &CutType) -> Vec<Segment> {
+    let third = |a: Xy| a.shift_xy((q.x - a.x) * 2.0 / 3.0, (q.y - a.y) * 2.0 / 3.0);
+    cubic([from, third(from), third(to), to], tp)
+}
+
+/// Segments of the path data
+fn path_segments(d: &str, tp: &CutType) -> Result<Vec<Segment>> {
+    let mut res = Vec::new();
+    let mut pos = Xy::new(0.0, 0.0);
+    let mut start = pos;
+    // Last control point for smooth curves
+    let mut ctrl: Option<Xy> = None;
+
+    for cmd in Data::parse(d)?.iter() {
+        let (position, params, step) = match cmd {
+            Command::Close => {
+                res.extend(polyline(&[pos, start], tp));
+                pos = start;
+                ctrl = None;
+                continue;
+            }
+            Command::Move(p, v) | Command::Line(p, v) | Command::SmoothQuadraticCurve(p, v) => {
+                (p, v, 2)
+            }
+            Command::HorizontalLine(p, v) | Command::VerticalLine(p, v) => (p, v, 1),
+            Command::QuadraticCurve(p, v) | Command::SmoothCubicCurve(p, v) => (p, v, 4),
+            Command::CubicCurve(p, v) => (p, v, 6),
+            Command::EllipticalArc(p, v) => (p, v, 7),
+        };
+
+        for (i, chunk) in params.chunks_exact(step).enumerate() {
+            let v: Vec<f64> = chunk.iter().map(|n| *n as f64).collect();
+            let base = match position {
+                Position::Absolute => Xy::new(0.0, 0.0),
+                Position::Relative => pos,
+            };
+            let at = |k: usize| base.shift_xy(v[k], v[k + 1]);
+            let reflected = ctrl
+                .map(|c| Xy::new(2.0 * pos.x - c.x, 2.0 * pos.y - c.y))
+                .unwrap_or(pos);
+
+            let (end, segments, next_ctrl) = match cmd {
+                Command::Move(..) if i == 0 => {
+                    pos = at(0);
+                    start = pos;
+                    ctrl = None;
+                    continue;
+                }
+                // Coordinates after the first move are implicit lines
+                Command::Move(..) | Command::Line(..) => (at(0), polyline(&[pos, at(0)], tp), None),
+                Command::HorizontalLine(..) => {
+                    let end = Xy::new(base.x + v[0], pos.y);
+                    (end, polyline(&[pos, end], tp), None)
+                }
+                Command::VerticalLine(..) => {
+                    let end = Xy::new(pos.x, base.y + v[0]);
+                    (end, polyline(&[pos, end], tp), None)
+                }
+                Command::QuadraticCurve(..) => {
+                    (at(2), quadratic(pos, at(0), at(2), tp), Some(at(0)))
+                }
+                Command::SmoothQuadraticCurve(..) => {
+                    (at(0), quadratic(pos, reflected, at(0), tp), Some(reflected))
+                }
+                Command::CubicCurve(..) => {
+                    (at(4), cubic([pos, at(0), at(2), at(4)], tp), Some(at(2)))
+                }
+                Command::SmoothCubicCurve(..) => (
+                    at(2),
+                    cubic([pos, reflected, at(0), at(2)], tp),
+                    Some(at(0)),
+                ),
+                Command::EllipticalArc(..) => (at(5), arc(pos, &v, at(5), tp), None),
+                Command::Close => unreachable!(),
+            };
+
+            res.extend(segments);
+            pos = end;
+            ctrl = next_ctrl;
+        }
+    }
+
+    Ok(res)
+}
+
+/// Cut and bend segments of any SVG, transformations of groups are not supported
+pub fn read_segments(content: &str) -> Result<Vec<Segment>> {
+    let mut res = Vec::new();
+
+    for event in svg::read(content)? {
+        let (name, attrs) = match event {
+            Event::Tag(_, Type::End, _) => continue,
+            Event::Tag(name, _, attrs) => (name, attrs),
+            Event::Error(e) => bail!("Не удалось прочитать SVG: {e}"),
+            _ => continue,
+        };
+
+        let tp = cut_type(&attrs);
+        match name {
+            "path" => res.extend(path_segments(
+                attrs.get("d").map(|v| v.as_ref()).unwrap_or_default(),
+                &tp,
+            )?),
+            "line" => res.extend(polyline(
+                &[
+                    Xy::new(attr_f64(&attrs, "x1"), attr_f64(&attrs, "y1")),
+                    Xy::new(attr_f64(&attrs, "x2"), attr_f64(&attrs, "y2")),
+                ],
+                &tp,
+            )),
+            "polyline" | "polygon" => {
+                let mut points =
+                    parse_points(attrs.get("points").map(|v| v.as_ref()).unwrap_or_default());
+                if name == "polygon" && !points.is_empty() {
+                    points.push(points[0]);
+                }
+                res.extend(polyline(&points, &tp));
+            }
+            "rect" => {
+                let at = Xy::new(attr_f64(&attrs, "x"), attr_f64(&attrs, "y"));
+                let (w, h) = (attr_f64(&attrs, "width"), attr_f64(&attrs, "height"));
+                let corners = [at, at.shift_x(w), at.shift_xy(w, h), at.shift_y(h), at];
+                res.extend(polyline(&corners, &tp));
+            }
+            "circle" => {
+                let center = Xy::new(attr_f64(&attrs, "cx"), attr_f64(&attrs, "cy"));
+                let r = Decimal::from_f64(attr_f64(&attrs, "r")).unwrap_or_default();
+                if r > Decimal::ZERO {
+                    res.extend(draw_circle(center.point(), r, &tp).map(Segment::Arc));
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::common::args::cli_help_arg;
+use crate::common::DrawResult;
+use crate::output;
+
+pub const CLI_SUBCOMMAND: &str = "regen";
+
+pub struct SourceFile;
+impl SourceFile {
+    const NAME: &'static str = "source";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .required(true)
+            .value_name("SVG")
+            .help("SVG файл созданный boxcut.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<String> {
+        m.get_one::<String>(Self::NAME).cloned()
+    }
+}
+
+pub struct Override;
+impl Override {
+    const NAME: &'static str = "override";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("override")
+            .short('o')
+            .value_name("ПАРАМЕТР=ЗНАЧЕНИЕ")
+            .action(ArgAction::Append)
+            .help("Заменить значение параметра, например -o length=400 или -o l=400.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Vec<String> {
+        m.get_many::<String>(Self::NAME)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn cli_build(root: Command) -> Command {
+    let c = Command::new(CLI_SUBCOMMAND)
+        .about("Пересоздать модель по параметрам из SVG файла.")
+        .arg(cli_help_arg())
+        .arg_required_else_help(true)
+        .arg(SourceFile::arg())
+        .arg(Override::arg());
+
+    root.subcommand(c)
+}
+
+pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
+    let source = SourceFile::extract(m).unwrap();
+    let content = std::fs::read_to_string(&source)?;
+
+    let (mut params, version) = output::read_params(&content)?;
+    log::info!(
+        "Модель {} из {} (boxcut {})",
+        params.model,
+        source,
+        if version.is_empty() { "?" } else { &version }
+    );
+
+    if version != env!("CARGO_PKG_VERSION") {
+        log::warn!("Файл создан другой версией boxcut, результат может отличаться от исходного");
+    }
+
+    for item in Override::extract(m) {
+        let Some((name, value)) = item.split_once('=') else {
+            bail!("Ожидалось ПАРАМЕТР=ЗНАЧЕНИЕ, а получено: {item}");
+        };
+        params.set(name.trim(), value.trim());
+    }
+
+    crate::draw_params(&params)
+}
@@ -0,0 +1,357 @@
+use std::fmt::Write;
+use std::io::Read;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::common::args::{cli_help_arg, is_flag, ModelParams};
+use crate::output::{self, xml_escape, OutputFormat};
+
+pub const CLI_SUBCOMMAND: &str = "serve";
+
+/// Query parameter with the model name
+const PARAM_MODEL: &str = "model";
+/// Query parameter with the output format
+const PARAM_OUTPUT: &str = "output";
+
+/// Largest POST body (bytes), a form with all parameters is far smaller
+const MAX_BODY: usize = 64 * 1024;
+
+/// POST body over `MAX_BODY`, answered with 413
+#[derive(Debug)]
+struct BodyTooLarge;
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Тело запроса больше {MAX_BODY} байт")
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+pub struct Port;
+impl Port {
+    const NAME: &'static str = "port";
+
+    const DEFAULT: &'static str = "8080";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("port")
+            .value_parser(value_parser!(u16))
+            .default_value(Self::DEFAULT)
+            .help("Порт HTTP сервера.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<u16> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct Host;
+impl Host {
+    const NAME: &'static str = "host";
+
+    const DEFAULT: &'static str = "127.0.0.1";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("host")
+            .default_value(Self::DEFAULT)
+            .help("Адрес для входящих соединений. 0.0.0.0 чтобы открыть доступ из локальной сети.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<String> {
+        m.get_one::<String>(Self::NAME).cloned()
+    }
+}
+
+pub fn cli_build(root: Command) -> Command {
+    let c = Command::new(CLI_SUBCOMMAND)
+        .about("Локальный HTTP сервер с формой для создания моделей.")
+        .arg(cli_help_arg())
+        .arg(Port::arg())
+        .arg(Host::arg());
+
+    root.subcommand(c)
+}
+
+pub fn cli_run(m: &ArgMatches) -> Result<()> {
+    let addr = format!(
+        "{}:{}",
+        Host::extract(m).unwrap(),
+        Port::extract(m).unwrap()
+    );
+
+    let server =
+        Server::http(&addr).map_err(|e| anyhow!("Не удалось запустить сервер на {addr}: {e}"))?;
+
+    log::info!("Сервер запущен: http://{addr}/");
+
+    for request in server.incoming_requests() {
+        handle(request);
+    }
+
+    Ok(())
+}
+
+fn handle(mut request: Request) {
+    log::info!("{} {}", request.method(), request.url());
+
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+
+    let response = match (request.method(), path) {
+        (Method::Get, "/") => Ok(html_response(index_page())),
+        (Method::Get, "/draw") => draw(parse_query(query)),
+        // Body parameters are set after the query ones and win
+        (Method::Post, "/draw") => read_body(&mut request)
+            .map(|body| [parse_query(query), body].concat())
+            .and_then(draw),
+        _ => Err(anyhow!("Нет такой страницы: {path}")),
+    };
+
+    let response = response.unwrap_or_else(|e| {
+        log::warn!("{e}");
+        let status = if e.is::<BodyTooLarge>() { 413 } else { 400 };
+        Response::from_data(e.to_string().into_bytes())
+            .with_status_code(status)
+            .with_header(header("Content-Type", "text/plain; charset=utf-8"))
+    });
+
+    if let Err(e) = request.respond(response) {
+        log::warn!("Ответ не отправлен: {e}");
+    }
+}
+
+/// Draw model with request parameters and render it in requested format
+fn draw(values: Vec<(String, String)>) -> Result<Response<std::io::Cursor<Vec<u8>>>> {
+    let mut params = ModelParams::default();
+    let mut format = OutputFormat::Svg;
+
+    for (name, value) in values {
+        match name.as_str() {
+            PARAM_MODEL => params.model = value,
+            PARAM_OUTPUT => format = OutputFormat::from_name(&value)?,
+            // Empty form fields are treated as not set
+            _ if value.is_empty() => {}
+            _ => params.set(&name, &value),
+        }
+    }
+
+    if params.model.is_empty() {
+        bail!("Не указана модель (параметр {PARAM_MODEL})");
+    }
+
+    let drawing = crate::draw_params(&params)?;
+
+    let file_name = std::path::Path::new(&drawing.default_file_name)
+        .with_extension(format.extension())
+        .to_string_lossy()
+        .to_string();
+
+    Ok(
+        Response::from_data(output::render(format, &drawing, output::DEFAULT_PRECISION)?)
+            .with_header(header("Content-Type", format.mime()))
+            .with_header(header(
+                "Content-Disposition",
+                &format!("inline; filename=\"{file_name}\""),
+            )),
+    )
+}
+
+/// Parameters from the POST body, either JSON or url encoded form
+fn read_body(request: &mut Request) -> Result<Vec<(String, String)>> {
+    if request.body_length().is_some_and(|len| len > MAX_BODY) {
+        return Err(BodyTooLarge.into());
+    }
+    // Length could be unknown for chunked body, one byte more tells it is too long
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)?;
+    if body.len() > MAX_BODY {
+        return Err(BodyTooLarge.into());
+    }
+    let body = String::from_utf8(body)?;
+
+    if !body.trim_start().starts_with('{') {
+        return Ok(parse_query(&body));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&body)?;
+    let Some(obj) = json.as_object() else {
+        bail!("Ожидался JSON объект");
+    };
+
+    let mut values = Vec::new();
+    for (name, value) in obj.iter() {
+        match (name.as_str(), value) {
+            ("params", serde_json::Value::Object(params)) => {
+                for (name, value) in params.iter() {
+                    values.push((name.clone(), json_value(value)?));
+                }
+            }
+            _ => values.push((name.clone(), json_value(value)?)),
+        }
+    }
+
+    Ok(values)
+}
+
+fn json_value(value: &serde_json::Value) -> Result<String> {
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        serde_json::Value::Bool(b) => Ok(b.to_string()),
+        _ => bail!("Неподдерживаемое значение параметра: {value}"),
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|v| !v.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (url_decode(name), url_decode(value))
+        })
+        .collect()
+}
+
+fn url_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn html_response(html: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_data(html.into_bytes())
+        .with_header(header("Content-Type", "text/html; charset=utf-8"))
+}
+
+/// Page with a form for every model
+fn index_page() -> String {
+    let cli = crate::cli_build();
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+        <title>boxcut</title></head><body>\n<h1>boxcut</h1>\n",
+    );
+
+    for model in crate::MODELS {
+        let cmd = cli.find_subcommand(model).unwrap();
+        let about = cmd.get_about().map(|v| v.to_string()).unwrap_or_default();
+
+        let _ = write!(
+            html,
+            "<form action=\"/draw\" method=\"get\" target=\"_blank\">\n\
+            <h2>{}</h2>\n<p>{}</p>\n\
+            <input type=\"hidden\" name=\"{PARAM_MODEL}\" value=\"{}\">\n<table>\n",
+            xml_escape(model),
+            xml_escape(&about),
+            xml_escape(model),
+        );
+
+        for arg in cmd.get_arguments() {
+            let id = arg.get_id().as_str();
+            if id == "help" {
+                continue;
+            }
+
+            let help = arg.get_help().map(|v| v.to_string()).unwrap_or_default();
+            let input = if is_flag(arg) {
+                format!("<input type=\"checkbox\" name=\"{}\">", xml_escape(id))
+            } else {
+                let default = arg
+                    .get_default_values()
+                    .first()
+                    .map(|v| v.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                format!(
+                    "<input name=\"{}\" value=\"{}\"{}>",
+                    xml_escape(id),
+                    xml_escape(&default),
+                    if arg.is_required_set() {
+                        " required"
+                    } else {
+                        ""
+                    }
+                )
+            };
+
+            let _ = writeln!(
+                html,
+                "<tr><td><label>{}</label></td><td>{}</td><td>{}</td></tr>",
+                xml_escape(id),
+                input,
+                xml_escape(&help)
+            );
+        }
+
+        let _ = write!(
+            html,
+            "<tr><td>{PARAM_OUTPUT}</td><td><select name=\"{PARAM_OUTPUT}\">"
+        );
+        for format in OutputFormat::ALL {
+            let _ = write!(html, "<option>{}</option>", format.extension());
+        }
+        html.push_str(
+            "</select></td><td></td></tr>\n</table>\n<button type=\"submit\">Создать</button>\n</form>\n",
+        );
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(values: &[(&str, &str)]) -> Result<()> {
+        draw(
+            values
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn only_models_are_drawn() {
+        for model in ["serve", "regen", "check", "sweep"] {
+            let e = request(&[("model", model), ("source", "x")]).unwrap_err();
+            assert!(e.to_string().contains("Неизвестная модель"), "{model}: {e}");
+        }
+        assert!(request(&[("model", "lid"), ("source", "x")]).is_err());
+        assert!(request(&[("model", "lid"), ("l", "300"), ("w", "200"), ("h", "50")]).is_ok());
+    }
+}
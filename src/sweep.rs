@@ -0,0 +1,490 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::common::args::{cli_help_arg, find_arg, is_flag, ArgsGlobal, ModelParams};
+use crate::common::report::{Report, ReportFormat};
+use crate::output::OutputFormat;
+
+pub const CLI_SUBCOMMAND: &str = "sweep";
+
+const INDEX_FILE_NAME: &str = "index.csv";
+
+/// Files of one sweep, protects from a typo in a range filling the disk
+const MAX_COMBINATIONS: usize = 1000;
+
+pub struct Template;
+impl Template {
+    const NAME: &'static str = "template";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("name")
+            .help("Шаблон имени файла, {model} - модель, {date} - дата, {material} - материал, {ПАРАМЕТР} - значение параметра, например {l} или {thickness}. По умолчанию модель и размеры.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<String> {
+        m.get_one::<String>(Self::NAME).cloned()
+    }
+}
+
+pub struct OutDir;
+impl OutDir {
+    const NAME: &'static str = "dir";
+
+    const DEFAULT: &'static str = ".";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("dir")
+            .value_parser(value_parser!(PathBuf))
+            .default_value(Self::DEFAULT)
+            .help("Папка для файлов и списка index.csv, будет создана если ее нет.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> PathBuf {
+        m.get_one(Self::NAME).cloned().unwrap_or_default()
+    }
+}
+
+pub struct CsvFile;
+impl CsvFile {
+    const NAME: &'static str = "csv";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME).long("csv").value_name("CSV").help(
+            "Таблица размеров: в первой строке имена параметров, в каждой следующей - одна модель.",
+        )
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<String> {
+        m.get_one::<String>(Self::NAME).cloned()
+    }
+}
+
+pub struct ModelArgs;
+impl ModelArgs {
+    const NAME: &'static str = "model";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .required(true)
+            .value_name("МОДЕЛЬ ПАРАМЕТРЫ")
+            .num_args(1..)
+            .trailing_var_arg(true)
+            .allow_hyphen_values(true)
+            .action(ArgAction::Append)
+            .help("Модель и ее параметры. Значение 200..400:50 - от 200 до 400 с шагом 50, 200,250 - перечисление.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Vec<String> {
+        m.get_many::<String>(Self::NAME)
+            .map(|v| v.cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn cli_build(root: Command) -> Command {
+    let c = Command::new(CLI_SUBCOMMAND)
+        .about("Нарисовать модель для всех сочетаний размеров, каждое в свой файл.")
+        .arg(cli_help_arg())
+        .arg_required_else_help(true)
+        .arg(Template::arg())
+        .arg(OutDir::arg())
+        .arg(CsvFile::arg())
+        .arg(ModelArgs::arg());
+
+    root.subcommand(c)
+}
+
+pub fn cli_run(m: &ArgMatches, globs: &ArgsGlobal) -> Result<()> {
+    if let Some(file) = &globs.file {
+        bail!("Файл {file} не используется в sweep, задайте имена шаблоном --name и папку --dir");
+    }
+    globs.check_machine()?;
+    let cli = crate::cli_build();
+    let base = parse_params(&cli, &ModelArgs::extract(m))?;
+
+    let rows = match CsvFile::extract(m) {
+        Some(file) => read_csv(&base, &std::fs::read_to_string(file)?)?,
+        None => vec![base],
+    };
+    let runs: Vec<ModelParams> = rows
+        .iter()
+        .map(combinations)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    if runs.len() > MAX_COMBINATIONS {
+        bail!(
+            "Сочетаний параметров {}, больше {MAX_COMBINATIONS}, разбейте на несколько запусков",
+            runs.len()
+        );
+    }
+    log::info!("Сочетаний параметров: {}", runs.len());
+
+    let template = Template::extract(m);
+    let vars = globs.file_vars();
+    let dir = OutDir::extract(m);
+
+    // Everything is drawn and named before the first file is written
+    let mut drawn = Vec::new();
+    let mut names = HashSet::new();
+    let mut failed = 0;
+    for params in runs {
+        let drawing = match crate::draw_params(&params) {
+            Ok(drawing) => drawing,
+            Err(e) => {
+                failed += 1;
+                log::error!("{}: {e}", describe(&params));
+                continue;
+            }
+        };
+        // Values of all parameters including defaults
+        let params = drawing.params.clone().unwrap_or(params);
+
+        let name = match template.as_deref() {
+            Some(template) => params.fill(&cli, template, &vars)?,
+            None => drawing.default_file_name.clone(),
+        };
+        let manifest = globs.manifest.then(|| manifest_name(&name));
+        if manifest.as_ref() == Some(&name) {
+            bail!("Для --manifest нужен файл чертежа, а не {name}, список деталей запишется рядом в .json");
+        }
+        for name in std::iter::once(&name).chain(manifest.as_ref()) {
+            if !names.insert(name.clone()) {
+                bail!("Имя файла {name} повторяется, добавьте в шаблон все изменяемые параметры");
+            }
+        }
+        drawn.push((name, manifest, drawing, params));
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    let mut written: Vec<(String, ModelParams)> = Vec::new();
+    let mut reports = Vec::new();
+    for (name, manifest, drawing, params) in drawn {
+        for issue in drawing.outline_issues() {
+            log::warn!("{name}: {issue}");
+        }
+        crate::write_file(dir.join(&name).to_str().unwrap(), &drawing, globs.precision)?;
+        if let Some(manifest) = manifest {
+            crate::write_file(
+                dir.join(manifest).to_str().unwrap(),
+                &drawing,
+                globs.precision,
+            )?;
+        }
+
+        if let Some(format) = globs.report {
+            let report = Report::new(&drawing, &globs.machine);
+            match format {
+                ReportFormat::Text => log::info!("{name}: {report}"),
+                ReportFormat::Json => {
+                    let mut json = report.to_json();
+                    json["file"] = json!(name);
+                    reports.push(json);
+                }
+            }
+        }
+        written.push((name, params));
+    }
+    // Drawings are in files, the console has room for all reports as one JSON array
+    if globs.report == Some(ReportFormat::Json) {
+        println!("{:#}", json!(reports));
+    }
+
+    let index = dir.join(INDEX_FILE_NAME);
+    std::fs::write(&index, index_csv(&written))?;
+    log::info!(
+        "Создано файлов: {}, список в {}",
+        written.len(),
+        index.display()
+    );
+
+    if failed > 0 {
+        bail!("Не удалось нарисовать сочетаний: {failed}");
+    }
+    Ok(())
+}
+
+/// Model parameters from the command line, values are not checked yet
+fn parse_params(root: &Command, args: &[String]) -> Result<ModelParams> {
+    let Some((model, args)) = args.split_first() else {
+        bail!("Не указана модель");
+    };
+    let Some(cmd) = root
+        .find_subcommand(model)
+        .filter(|_| crate::MODELS.contains(&model.as_str()))
+    else {
+        bail!("Неизвестная модель: {model}");
+    };
+
+    let mut params = ModelParams::new(model);
+    let mut args = args.iter();
+    while let Some(item) = args.next() {
+        let Some(name) = item.strip_prefix("--").or(item.strip_prefix('-')) else {
+            bail!("Ожидался параметр, а получено: {item}");
+        };
+        if let Some((name, value)) = name.split_once('=') {
+            params.set(name, value);
+            continue;
+        }
+
+        let Some(arg) = find_arg(cmd, name) else {
+            bail!("Неизвестный параметр {name} для модели {model}");
+        };
+        if is_flag(arg) {
+            params.set(name, "true");
+        } else {
+            let Some(value) = args.next() else {
+                bail!("Нет значения параметра {name}");
+            };
+            params.set(name, value);
+        }
+    }
+
+    Ok(params)
+}
+
+/// One set of parameters per CSV row on top of the `base`
+fn read_csv(base: &ModelParams, content: &str) -> Result<Vec<ModelParams>> {
+    let mut lines = content.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    let Some(header) = lines.next() else {
+        bail!("Пустой CSV файл");
+    };
+    let names: Vec<&str> = header.split(',').map(|n| n.trim()).collect();
+
+    lines
+        .map(|line| {
+            let values: Vec<&str> = line.split(',').map(|v| v.trim()).collect();
+            if values.len() != names.len() {
+                bail!(
+                    "В строке CSV {} значений вместо {}: {line}",
+                    values.len(),
+                    names.len()
+                );
+            }
+
+            let mut params = base.clone();
+            for (name, value) in names.iter().zip(values) {
+                params.set(name, value);
+            }
+            Ok(params)
+        })
+        .collect()
+}
+
+/// All combinations of the swept values
+fn combinations(params: &ModelParams) -> Result<Vec<ModelParams>> {
+    let mut result = vec![ModelParams::new(&params.model)];
+    for (name, value) in params.values.iter() {
+        let values = sweep_values(value)?;
+        if result.len() * values.len() > MAX_COMBINATIONS {
+            bail!("Больше {MAX_COMBINATIONS} сочетаний параметров, разбейте на несколько запусков");
+        }
+        result = result
+            .into_iter()
+            .flat_map(|p| {
+                values.iter().map(move |v| {
+                    let mut p = p.clone();
+                    p.set(name, v);
+                    p
+                })
+            })
+            .collect();
+    }
+
+    Ok(result)
+}
+
+/// Values of `from..to:step` range, `a,b,c` list or a single value
+fn sweep_values(value: &str) -> Result<Vec<String>> {
+    if let Some((from, to)) = value.split_once("..") {
+        let Some((to, step)) = to.split_once(':') else {
+            bail!("Нет шага диапазона, ожидалось ОТ..ДО:ШАГ, а получено: {value}");
+        };
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<Decimal>()
+                .map_err(|_| anyhow::anyhow!("Ожидалось число в диапазоне {value}: {v}"))
+        };
+        let (from, to, step) = (parse(from)?, parse(to)?, parse(step)?);
+        if step <= Decimal::ZERO || from > to {
+            bail!("Диапазон {value} пуст, начало должно быть не больше конца, а шаг больше 0");
+        }
+        if (to - from) / step >= Decimal::from(MAX_COMBINATIONS) {
+            bail!("В диапазоне {value} больше {MAX_COMBINATIONS} значений");
+        }
+
+        let mut values = Vec::new();
+        let mut v = from;
+        while v <= to {
+            values.push(v.to_string());
+            v += step;
+        }
+        return Ok(values);
+    }
+
+    Ok(value.split(',').map(|v| v.trim().to_string()).collect())
+}
+
+/// JSON list of panels next to the drawing file
+fn manifest_name(name: &str) -> String {
+    Path::new(name)
+        .with_extension(OutputFormat::Json.extension())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Parameters given by user for the log
+fn describe(params: &ModelParams) -> String {
+    let values: Vec<String> = params
+        .values
+        .iter()
+        .map(|(n, v)| format!("{n}={v}"))
+        .collect();
+    [params.model.clone(), values.join(" ")]
+        .join(" ")
+        .trim_end()
+        .to_string()
+}
+
+/// List of generated files with parameters of each
+fn index_csv(written: &[(String, ModelParams)]) -> String {
+    let mut columns: Vec<&str> = Vec::new();
+    for (_, params) in written {
+        for (name, _) in params.values.iter() {
+            if !columns.contains(&name.as_str()) {
+                columns.push(name);
+            }
+        }
+    }
+
+    let row = |fields: Vec<&str>| {
+        let fields: Vec<String> = fields.into_iter().map(csv_field).collect();
+        fields.join(",") + "\n"
+    };
+
+    let mut csv = row([vec!["file", "model"], columns.clone()].concat());
+    for (file, params) in written {
+        let values: Vec<&str> = columns
+            .iter()
+            .map(|c| {
+                params
+                    .values
+                    .iter()
+                    .find(|(n, _)| n == c)
+                    .map(|(_, v)| v.as_str())
+                    .unwrap_or_default()
+            })
+            .collect();
+        csv.push_str(&row(
+            [vec![file.as_str(), params.model.as_str()], values].concat()
+        ));
+    }
+
+    csv
+}
+
+/// Field quoted when it has a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_includes_both_ends() {
+        assert_eq!(sweep_values("200..300:50").unwrap(), ["200", "250", "300"]);
+        assert_eq!(sweep_values("1..2:0.4").unwrap(), ["1", "1.4", "1.8"]);
+        assert_eq!(
+            sweep_values("square,rounded").unwrap(),
+            ["square", "rounded"]
+        );
+        assert_eq!(sweep_values("2.3").unwrap(), ["2.3"]);
+        assert!(sweep_values("300..200:50").is_err());
+        assert!(sweep_values("200..300").is_err());
+        assert!(sweep_values("1..100000:1").is_err());
+    }
+
+    #[test]
+    fn every_combination_is_drawn() {
+        let cli = crate::cli_build();
+        let args = [
+            "box-cuboid",
+            "-l",
+            "200..300:100",
+            "-w",
+            "100,150",
+            "-h",
+            "80",
+        ];
+        let params = parse_params(&cli, &args.map(String::from)).unwrap();
+
+        let runs = combinations(&params).unwrap();
+        let names: Vec<String> = runs
+            .iter()
+            .map(|p| p.fill(&cli, "{model}_{l}x{width}x{h}", &[]).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "box-cuboid_200x100x80",
+                "box-cuboid_200x150x80",
+                "box-cuboid_300x100x80",
+                "box-cuboid_300x150x80",
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_rows_on_top_of_command_line() {
+        let cli = crate::cli_build();
+        let base = parse_params(&cli, &["lid", "-t", "3"].map(String::from)).unwrap();
+
+        let rows = read_csv(&base, "l,w,h\n300,200,40\n\n250, 150, 30\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get(&cli, "length").unwrap(), Some("250"));
+        assert_eq!(rows[1].get(&cli, "thickness").unwrap(), Some("3"));
+        assert!(read_csv(&base, "l,w\n300\n").is_err());
+    }
+
+    #[test]
+    fn too_many_combinations() {
+        let cli = crate::cli_build();
+        let args = [
+            "box-cuboid",
+            "-l",
+            "100..199:1",
+            "-w",
+            "100..199:1",
+            "-h",
+            "80",
+        ];
+        let params = parse_params(&cli, &args.map(String::from)).unwrap();
+        assert!(combinations(&params).is_err());
+    }
+
+    #[test]
+    fn index_fields_are_quoted() {
+        let mut params = ModelParams::new("lid");
+        params.set("l", "300");
+        params.set("material", "картон \"5,5\"");
+        let csv = index_csv(&[("lid,300.svg".to_string(), params)]);
+        assert_eq!(
+            csv,
+            "file,model,l,material\n\"lid,300.svg\",lid,300,\"картон \"\"5,5\"\"\"\n"
+        );
+    }
+}
@@ -1,29 +1,226 @@
-use anyhow::Result;
-use clap::{ArgMatches, Command};
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
 use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::common::args::{
+    cli_help_arg, GlueFlap, GlueFlapAngle, GlueFlapStyle, HandleHeight, HandleOffset, HandleShape,
+    HandleWidth, NotchOn, Thickness, ThumbNotch, Width,
+};
+use crate::common::{
+    dec, file_name, Borders, CutType, DrawResult, FlapStyle, Handle, HandleElement, Net, Notch,
+    NotchPlace, Origin, Point, PolygonElement, Role, Side, Square, SquareElement, VIEWPORT_OFFSET,
+};
+use crate::lid::{InnerWalls, LidBorders, LidHeight};
+
+/// Inner height and length for 12", 10" and 7" records: sleeve plus 15mm
+const INNER_12: Decimal = dec(330, 0);
+const INNER_10: Decimal = dec(280, 0);
+const INNER_7: Decimal = dec(195, 0);
+const STRIPE_H: Decimal = dec(90, 0);
+const STRIPE_HANDLE_TOP_OFFSET: Decimal = dec(35, 0);
+/// Space between the stripes on the sheet
+const STRIPE_GAP: Decimal = dec(5, 0);
+
+/// Label tab above the divider card
+const TAB_H: Decimal = dec(15, 0);
+
+/// Model name in the file name of the dividers sheet
+const DIVIDERS_FILE_NAME: &str = "vinyl-dividers";
 
-use crate::common::args::{cli_help_arg, GlueFlap, Thickness, Width};
-use crate::common::{Borders, CutType, DrawResult, Origin, Point, SquareElement, VIEWPORT_OFFSET};
-use crate::lid::LidHeight;
+pub const CLI_SUBCOMMAND: &str = "vinyl";
 
-const INNER_H: f64 = 330.0;
-const INNER_L: f64 = 330.0;
-const STRIPE_H: f64 = 90.0;
-const STRIPE_HANDLE_TOP_OFFSET: f64 = 35.0;
+pub struct NoStripe;
+impl NoStripe {
+    const NAME: &'static str = "nostripe";
 
-const VINYL_FIE_NAME: &str = "LaserCutVinylBox.svg";
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("no-stripe")
+            .action(ArgAction::SetTrue)
+            .help("Без усиливающей ленты по низу коробки.")
+    }
 
-pub const CLI_SUBCOMMAND: &str = "vinyl";
+    pub fn extract(m: &ArgMatches) -> bool {
+        m.get_flag(Self::NAME)
+    }
+}
+
+pub struct Dividers;
+impl Dividers {
+    const NAME: &'static str = "dividers";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("dividers")
+            .value_parser(value_parser!(u32))
+            .help("Количество карточек-разделителей с язычками для подписи (жанры и т.п.).")
+    }
+
+    pub fn extract(m: &ArgMatches) -> u32 {
+        m.get_one(Self::NAME).copied().unwrap_or_default()
+    }
+}
+
+pub struct DividerTabs;
+impl DividerTabs {
+    const NAME: &'static str = "tabs";
+
+    const DEFAULT: &'static str = "3";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("tabs")
+            .value_parser(value_parser!(u32).range(1..))
+            .default_value(Self::DEFAULT)
+            .help("Количество положений язычка, язычки соседних карточек не закрывают друг друга.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> u32 {
+        m.get_one(Self::NAME).copied().unwrap_or(1)
+    }
+}
+
+pub struct DividersOnly;
+impl DividersOnly {
+    const NAME: &'static str = "dividersonly";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("dividers-only")
+            .action(ArgAction::SetTrue)
+            .requires(Dividers::NAME)
+            .help("Только разделители, на отдельном листе без коробки.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> bool {
+        m.get_flag(Self::NAME)
+    }
+}
+
+pub struct RecordFormat;
+impl RecordFormat {
+    const NAME: &'static str = "format";
+
+    const DEFAULT: &'static str = "12";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("format")
+            .value_parser(["7", "10", "12"])
+            .default_value(Self::DEFAULT)
+            .help("Размер пластинок (дюймы), задает внутреннюю высоту и длину коробки.")
+    }
+
+    /// Inner height and length of the box
+    pub fn extract(m: &ArgMatches) -> Decimal {
+        match m.get_one::<String>(Self::NAME).map(|v| v.as_str()) {
+            Some("7") => INNER_7,
+            Some("10") => INNER_10,
+            _ => INNER_12,
+        }
+    }
+}
+
+pub struct InnerHeight;
+impl InnerHeight {
+    const NAME: &'static str = "innerheight";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("inner-height")
+            .value_parser(value_parser!(Decimal))
+            .help("Своя внутренняя высота (мм) вместо размера из --format.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct InnerLength;
+impl InnerLength {
+    const NAME: &'static str = "innerlength";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("inner-length")
+            .value_parser(value_parser!(Decimal))
+            .help("Своя внутренняя длина (мм) вместо размера из --format.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct Records;
+impl Records {
+    const NAME: &'static str = "records";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("records")
+            .value_parser(value_parser!(u32).range(1..))
+            .conflicts_with(Width::NAME)
+            .help("Количество пластинок, ширина коробки считается по толщине конверта.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<u32> {
+        m.get_one(Self::NAME).copied()
+    }
+}
+
+pub struct SleeveThickness;
+impl SleeveThickness {
+    const NAME: &'static str = "sleeve";
+
+    const DEFAULT: &'static str = "3.5";
+
+    pub fn arg() -> Arg {
+        Arg::new(Self::NAME)
+            .long("sleeve")
+            .value_parser(value_parser!(Decimal))
+            .default_value(Self::DEFAULT)
+            .help("Толщина одной пластинки в конверте (мм), для --records.")
+    }
+
+    pub fn extract(m: &ArgMatches) -> Option<Decimal> {
+        m.get_one(Self::NAME).copied()
+    }
+}
 
 pub fn cli_build(root: Command) -> Command {
     let c = Command::new(CLI_SUBCOMMAND)
         .about("Коробка для виниловых пластинок.")
         .arg(cli_help_arg())
         .arg_required_else_help(true)
-        .arg(Width::arg())
+        .arg(
+            Width::arg()
+                .required(false)
+                .required_unless_present(Records::NAME),
+        )
+        .arg(Records::arg())
+        .arg(SleeveThickness::arg())
+        .arg(RecordFormat::arg())
+        .arg(InnerHeight::arg())
+        .arg(InnerLength::arg())
         .arg(LidHeight::arg())
+        .arg(LidBorders::arg())
         .arg(Thickness::arg())
-        .arg(GlueFlap::arg());
+        .arg(GlueFlap::arg())
+        .arg(GlueFlapStyle::arg())
+        .arg(GlueFlapAngle::arg())
+        .arg(HandleShape::arg())
+        .arg(HandleHeight::arg())
+        .arg(HandleWidth::arg())
+        .arg(HandleOffset::arg())
+        .arg(ThumbNotch::arg())
+        .arg(NotchOn::arg())
+        .arg(NoStripe::arg())
+        .arg(Dividers::arg())
+        .arg(DividerTabs::arg())
+        .arg(DividersOnly::arg());
 
     root.subcommand(c)
 }
@@ -38,34 +235,124 @@ pub fn cli_draw(m: &ArgMatches) -> Result<DrawResult> {
 
 #[derive(Debug)]
 pub struct VinylBoxCfg {
-    thickness: f64,
-    glue_flap: f64,
-    lid_height: f64,
-    height: f64,
-    length: f64,
-    width: f64,
+    thickness: Decimal,
+    glue_flap: Decimal,
+    flap_style: FlapStyle,
+    handle: Handle,
+    /// Thumb notch to open the lid
+    notch: Option<Notch>,
+    /// Reinforcement band with the handles
+    stripe: bool,
+    /// Number of divider cards
+    dividers: u32,
+    /// Tab positions across the card
+    tabs: u32,
+    /// Dividers on their own sheet
+    dividers_only: bool,
+    lid_height: Decimal,
+    /// Double side walls of the lid
+    lid_fat: bool,
+    height: Decimal,
+    length: Decimal,
+    width: Decimal,
 }
 
 impl VinylBoxCfg {
-    pub fn thick_n(&self, multiply: usize) -> f64 {
-        self.thickness * multiply as f64
+    pub fn thick_n(&self, multiply: usize) -> Decimal {
+        self.thickness * Decimal::from(multiply)
+    }
+
+    /// Lid side walls stick out of the box by this, inner layers of the fat lid are next to them
+    fn lid_side(&self) -> Decimal {
+        if self.lid_fat {
+            self.lid_height * Decimal::TWO + self.thick_n(2)
+        } else {
+            self.lid_height + self.thickness
+        }
     }
 }
 
 impl VinylBoxCfg {
     pub fn from(m: &ArgMatches) -> Result<Self> {
-        let thickn = Thickness::extract(m).unwrap().to_f64().unwrap();
+        let thickn = Thickness::extract(m).unwrap();
+        let inner = RecordFormat::extract(m);
+        let inner_h = InnerHeight::extract(m).unwrap_or(inner);
+        let inner_l = InnerLength::extract(m).unwrap_or(inner);
+        if inner_h <= Decimal::ZERO || inner_l <= Decimal::ZERO {
+            bail!("Внутренние размеры должны быть больше 0: {inner_h}x{inner_l}");
+        }
+
+        let width = match Records::extract(m) {
+            Some(records) => {
+                let sleeve = SleeveThickness::extract(m).unwrap();
+                if sleeve <= Decimal::ZERO {
+                    bail!("Толщина конверта должна быть больше 0: {sleeve}");
+                }
+                // Records stand between two walls
+                let width = sleeve * Decimal::from(records) + thickn * Decimal::TWO;
+                log::info!("Ширина коробки на {records} пластинок: {width}мм");
+                width
+            }
+            None => Width::extract(m).unwrap(),
+        };
+
         Ok(Self {
-            thickness: Thickness::extract(m).unwrap().to_f64().unwrap(),
-            glue_flap: GlueFlap::extract(m).unwrap().to_f64().unwrap(),
-            lid_height: LidHeight::extract(m).unwrap().to_f64().unwrap(),
-            height: INNER_H + thickn * 3.0,
-            length: INNER_L + thickn * 4.0,
-            width: Width::extract(m).unwrap().to_f64().unwrap(),
+            thickness: Thickness::extract(m).unwrap(),
+            glue_flap: GlueFlap::extract(m)?,
+            flap_style: GlueFlapStyle::extract(m)?,
+            handle: Handle::from_matches(m)?,
+            notch: Notch::from_matches(m)?,
+            stripe: !NoStripe::extract(m),
+            dividers: Dividers::extract(m),
+            tabs: DividerTabs::extract(m),
+            dividers_only: DividersOnly::extract(m),
+            lid_height: LidHeight::extract(m).unwrap(),
+            lid_fat: LidBorders::extract(m),
+            height: inner_h + thickn * Decimal::from(3),
+            length: inner_l + thickn * Decimal::from(4),
+            width,
         })
     }
 }
 
+/// Card of `w` by `h` with the tab from `x0` to `x1` raised above the rest of the top side
+fn divider_outline(w: Decimal, h: Decimal, x0: Decimal, x1: Decimal) -> Vec<Point> {
+    let outline = [
+        (Decimal::ZERO, TAB_H),
+        (x0, TAB_H),
+        (x0, Decimal::ZERO),
+        (x1, Decimal::ZERO),
+        (x1, TAB_H),
+        (w, TAB_H),
+        (w, h),
+        (Decimal::ZERO, h),
+    ];
+
+    // Tab at the card end leaves repeated and straight angle vertices
+    let n = outline.len();
+    let mut vertices: Vec<(Decimal, Decimal)> = (0..n)
+        .filter(|i| outline[*i] != outline[(i + 1) % n])
+        .map(|i| outline[i])
+        .collect();
+    let n = vertices.len();
+    vertices = (0..n)
+        .filter(|i| {
+            let (prev, p, next) = (
+                vertices[(i + n - 1) % n],
+                vertices[*i],
+                vertices[(i + 1) % n],
+            );
+            !(prev.0 == p.0 && p.0 == next.0 || prev.1 == p.1 && p.1 == next.1)
+        })
+        .map(|i| vertices[i])
+        .collect();
+
+    vertices
+        .into_iter()
+        .map(|(x, y)| Point::new(x, y))
+        .collect()
+}
+
 struct VinylBox {
     cfg: VinylBoxCfg,
     offset: Point,
@@ -75,20 +362,37 @@ struct VinylBox {
 impl VinylBox {
     fn new(cfg: VinylBoxCfg) -> Self {
         // Initial offset
-        let offset =
-            Point::new(cfg.width + cfg.glue_flap, 0.0).shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
+        let offset = Point::new(
+            (cfg.width + cfg.glue_flap).max(cfg.lid_side()),
+            Decimal::ZERO,
+        )
+        .shift_xy(VIEWPORT_OFFSET, VIEWPORT_OFFSET);
+        let model = if cfg.dividers_only {
+            DIVIDERS_FILE_NAME
+        } else {
+            CLI_SUBCOMMAND
+        };
+        let mut result = DrawResult::empty(file_name(
+            model,
+            [cfg.length, cfg.width, cfg.height],
+            cfg.thickness,
+        ));
+        result.net = Net::new(cfg.thickness);
         Self {
             cfg,
             offset,
-            result: DrawResult::empty(VINYL_FIE_NAME.into()),
+            result,
         }
     }
 
     fn draw(mut self) -> DrawResult {
-        self.draw_top_lid();
-        self.draw_side_walls();
-        self.draw_main_walls();
-        self.draw_bottom_stripe();
+        if !self.cfg.dividers_only {
+            self.draw_top_lid();
+            self.draw_side_walls();
+            self.draw_main_walls();
+            self.draw_bottom_stripe();
+        }
+        self.draw_dividers();
 
         self.result
     }
@@ -101,25 +405,42 @@ impl VinylBox {
         SquareElement::cut(self.cfg.thick_n(2), self.cfg.thickness)
     }
 
-    fn main_wall_length(&self) -> f64 {
+    fn main_wall_length(&self) -> Decimal {
         // задняя стенка минус толщина круговой накладки
         self.cfg.length - self.cfg.thick_n(2)
     }
 
     fn draw_top_lid(&mut self) {
-        let lid_len = self.cfg.length + self.cfg.thick_n(2);
+        // Fat lid is wider by the inner layers of the side walls
+        let fat = if self.cfg.lid_fat { 1 } else { 0 };
+        let lid_len = self.cfg.length + self.cfg.thick_n(2 + fat * 2);
         let lid_width = self.cfg.width + self.cfg.thick_n(2);
-        let offset = self.offset.shift_nx(self.cfg.thick_n(1));
+        let offset = self.offset.shift_nx(self.cfg.thick_n(1 + fat));
 
         let top_flap = SquareElement::new(
-            lid_len - self.cfg.glue_flap * 2.0,
+            lid_len - self.cfg.glue_flap * Decimal::TWO,
             self.cfg.lid_height - self.cfg.thickness,
         )
         .with_borders(Borders::new_cut())
-        .border_bottom(CutType::Bend);
+        .border_bottom(CutType::Bend)
+        .role(Role::Flap);
 
-        self.result
-            .append(top_flap.draw(offset.shift_x(self.cfg.glue_flap)));
+        self.result.append(
+            top_flap
+                .named("lid-front-flap")
+                .hinged("lid-front", Side::Bottom, 180.0)
+                .draw(offset.shift_x(self.cfg.glue_flap)),
+        );
+
+        let notch = self
+            .cfg
+            .notch
+            .and_then(|n| n.placed(NotchPlace::Lid, top_flap.square.w, top_flap.square.h));
+        if let Some(notch) = notch {
+            self.result.append(
+                notch.draw_hole(offset.shift_xy(lid_len / Decimal::TWO, top_flap.square.h)),
+            );
+        }
 
         let top_flap_side_cut = SquareElement::new(self.cfg.glue_flap, top_flap.square.h)
             .with_borders(Borders::nope())
@@ -132,60 +453,106 @@ impl VinylBox {
 
         let offset = offset.shift_y(top_flap.square.h);
 
-        let lid_front_side = SquareElement::new(lid_len, self.cfg.lid_height).borders(
-            CutType::Nope,
-            CutType::Cut,
-            CutType::Bend,
-            CutType::Cut,
-        );
-
-        self.result.append(lid_front_side.draw(offset));
+        let lid_front_side = SquareElement::new(lid_len, self.cfg.lid_height)
+            .borders(CutType::Nope, CutType::Cut, CutType::Bend, CutType::Cut)
+            .role(Role::Wall);
 
-        let offset = offset.shift_y(lid_front_side.square.h);
-
-        let lid_top_wall = SquareElement::new(lid_len, lid_width).borders(
-            CutType::Nope,
-            CutType::Bend,
-            CutType::Bend,
-            CutType::Bend,
+        self.result.append(
+            lid_front_side
+                .named("lid-front")
+                .hinged("lid-top", Side::Bottom, 90.0)
+                .draw(offset),
         );
 
-        self.result.append(lid_top_wall.draw(offset));
+        let offset = offset.shift_y(lid_front_side.square.h);
 
-        let side_flap =
-            SquareElement::new(self.cfg.lid_height - self.cfg.thickness, self.cfg.glue_flap)
-                .borders(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut);
+        let lid_top_wall = SquareElement::new(lid_len, lid_width)
+            .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend)
+            .role(Role::Top);
 
         self.result.append(
-            side_flap.draw(
-                offset
-                    .shift_nx(self.cfg.thickness)
-                    .shift_y(self.cfg.thickness)
-                    .origin(Origin::BottomRight),
-            ),
+            lid_top_wall
+                .named("lid-top")
+                .hinged("back", Side::Bottom, 90.0)
+                .draw(offset),
         );
 
-        self.result.append(
-            side_flap.mirror_vertical().draw(
-                offset
-                    .shift_xy(lid_len + self.cfg.thickness, self.cfg.thickness)
-                    .origin(Origin::BottomLeft),
-            ),
-        );
+        let side_flap = self
+            .cfg
+            .flap_style
+            .flap(
+                Square::new(self.cfg.lid_height - self.cfg.thickness, self.cfg.glue_flap),
+                Borders::new(CutType::Cut, CutType::Cut, CutType::Bend, CutType::Cut),
+                Side::Bottom,
+            )
+            .map(|f| f.role(Role::GlueFlap));
+
+        if let Some(side_flap) = &side_flap {
+            self.result.append(
+                side_flap
+                    .named("lid-left-flap")
+                    .hinged_side("lid-left", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_nx(self.cfg.thickness)
+                            .shift_y(self.cfg.thickness)
+                            .origin(Origin::BottomRight),
+                    ),
+            );
+
+            self.result.append(
+                side_flap
+                    .mirror_vertical()
+                    .named("lid-right-flap")
+                    .hinged_side("lid-right", Side::Bottom, 90.0)
+                    .draw(
+                        offset
+                            .shift_xy(lid_len + self.cfg.thickness, self.cfg.thickness)
+                            .origin(Origin::BottomLeft),
+                    ),
+            );
+        }
 
         let lid_side_wall = SquareElement::new(self.cfg.lid_height, lid_width - self.cfg.thickness)
-            .borders(CutType::Nope, CutType::Nope, CutType::Cut, CutType::Cut);
+            .borders(CutType::Nope, CutType::Nope, CutType::Cut, CutType::Cut)
+            .role(Role::Wall)
+            .hinged("lid-top", Side::Right, 90.0);
+
+        let lid_side_wall = if self.cfg.lid_fat {
+            let inner = InnerWalls {
+                wall: lid_side_wall.square,
+                gaps: (self.cfg.thickness, self.cfg.thickness),
+                thickness: self.cfg.thickness,
+            };
+            self.result.append(inner.draw(
+                offset.shift_y(self.cfg.thickness),
+                offset.shift_xy(lid_len, self.cfg.thickness),
+                [
+                    ("lid-left", "lid-left-inner"),
+                    ("lid-right", "lid-right-inner"),
+                ],
+            ));
+            lid_side_wall.border_left(CutType::Nope)
+        } else {
+            lid_side_wall
+        };
 
         self.result.append(
-            lid_side_wall.draw(offset.shift_y(self.cfg.thick_n(1)).origin(Origin::TopRight)),
+            lid_side_wall
+                .named("lid-left")
+                .draw(offset.shift_y(self.cfg.thick_n(1)).origin(Origin::TopRight)),
         );
 
         self.result.append(
             lid_side_wall
                 .mirror_vertical()
+                .named("lid-right")
                 .draw(offset.shift_xy(lid_len, self.cfg.thickness)),
         );
 
+        self.result.net.glue("lid-left-flap", "lid-front");
+        self.result.net.glue("lid-right-flap", "lid-front");
+
         // Small cut offs
         self.result
             .append(self.square_cut().draw(offset.origin(Origin::TopRight)));
@@ -200,41 +567,55 @@ impl VinylBox {
         let offset = self.offset.shift_xy(self.cfg.thickness, self.cfg.thickness);
 
         let side_wall = SquareElement::new(self.cfg.width, self.cfg.height - self.cfg.thickness)
-            .borders(CutType::Cut, CutType::Nope, CutType::Bend, CutType::Bend);
-
-        let flap = SquareElement::new(self.cfg.glue_flap, side_wall.square.h).borders(
-            CutType::Cut,
-            CutType::Nope,
-            CutType::Cut,
-            CutType::Cut,
-        );
-
+            .borders(CutType::Cut, CutType::Nope, CutType::Bend, CutType::Bend)
+            .role(Role::Wall);
+
+        let flap = self
+            .cfg
+            .flap_style
+            .flap(
+                Square::new(self.cfg.glue_flap, side_wall.square.h),
+                Borders::new(CutType::Cut, CutType::Nope, CutType::Cut, CutType::Cut),
+                Side::Right,
+            )
+            .map(|f| f.role(Role::GlueFlap));
+
+        // Bottom flaps are glued all over, they keep the full size
         let flap_bot = SquareElement::new(
             self.cfg.width - self.cfg.thick_n(3),
-            (self.cfg.length - self.cfg.thick_n(4)) / 2.0,
+            (self.cfg.length - self.cfg.thick_n(4)) / Decimal::TWO,
         )
-        .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut);
+        .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut)
+        .role(Role::GlueFlap);
 
         let (side_off, handle) = self.handle_hole(true);
-        let handle_top_offset = if STRIPE_HANDLE_TOP_OFFSET < self.cfg.lid_height {
-            self.cfg.lid_height
-        } else {
-            STRIPE_HANDLE_TOP_OFFSET
-        };
+        let handle_top_offset = self.handle_top_offset();
 
-        self.result
-            .append(side_wall.draw(offset.origin(Origin::TopRight)));
+        self.result.append(
+            side_wall
+                .named("left")
+                .hinged("back", Side::Right, 90.0)
+                .draw(offset.origin(Origin::TopRight)),
+        );
 
-        self.result
-            .append(flap.draw(offset.shift_nx(side_wall.square.w).origin(Origin::TopRight)));
+        if let Some(flap) = &flap {
+            self.result.append(
+                flap.named("left-flap")
+                    .hinged_side("left", Side::Right, 90.0)
+                    .draw(offset.shift_nx(side_wall.square.w).origin(Origin::TopRight)),
+            );
+        }
 
         self.result.append(
-            flap_bot.draw(
-                offset
-                    .shift_nx(self.cfg.thickness)
-                    .shift_y(side_wall.square.h)
-                    .origin(Origin::TopRight),
-            ),
+            flap_bot
+                .named("left-bottom-flap")
+                .hinged("left", Side::Top, 90.0)
+                .draw(
+                    offset
+                        .shift_nx(self.cfg.thickness)
+                        .shift_y(side_wall.square.h)
+                        .origin(Origin::TopRight),
+                ),
         );
 
         self.result.append(
@@ -262,20 +643,36 @@ impl VinylBox {
 
         let roffset = offset.shift_x(self.cfg.length - self.cfg.thick_n(2));
 
-        self.result
-            .append(side_wall.mirror_vertical().draw(roffset));
-
         self.result.append(
-            flap.mirror_vertical()
-                .draw(roffset.shift_x(side_wall.square.w)),
+            side_wall
+                .mirror_vertical()
+                .named("right")
+                .hinged("back", Side::Left, 90.0)
+                .draw(roffset),
         );
 
+        if let Some(flap) = &flap {
+            self.result.append(
+                flap.mirror_vertical()
+                    .named("right-flap")
+                    .hinged_side("right", Side::Left, 90.0)
+                    .draw(roffset.shift_x(side_wall.square.w)),
+            );
+        }
+
         self.result.append(
             flap_bot
                 .mirror_vertical()
+                .named("right-bottom-flap")
+                .hinged("right", Side::Top, 90.0)
                 .draw(roffset.shift_xy(self.cfg.thickness, side_wall.square.h)),
         );
 
+        self.result.net.glue("left-flap", "front");
+        self.result.net.glue("right-flap", "front");
+        self.result.net.glue("left-bottom-flap", "bottom");
+        self.result.net.glue("right-bottom-flap", "bottom");
+
         self.result
             .append(self.square_cut().draw(roffset.shift_y(side_wall.square.h)));
 
@@ -307,35 +704,53 @@ impl VinylBox {
             self.main_wall_length(),
             self.cfg.height + self.cfg.thickness,
         )
-        .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend);
+        .borders(CutType::Nope, CutType::Bend, CutType::Bend, CutType::Bend)
+        .role(Role::Wall);
 
         self.result
             .append(self.square_cut_w().draw(offset.origin(Origin::TopRight)));
         self.result
             .append(self.square_cut_w().draw(offset.shift_x(back_wall.square.w)));
 
-        self.result.append(back_wall.draw(offset));
+        self.result.append(
+            back_wall
+                .named("back")
+                .hinged("bottom", Side::Bottom, 90.0)
+                .draw(offset),
+        );
 
         let offset = offset.shift_y(back_wall.square.h);
 
         let bot_wall = back_wall
             .height(self.cfg.width - self.cfg.thick_n(1))
             .border_left(CutType::Cut)
-            .border_right(CutType::Cut);
+            .border_right(CutType::Cut)
+            .role(Role::Bottom);
 
-        self.result.append(bot_wall.draw(offset));
+        self.result.append(bot_wall.named("bottom").draw(offset));
 
         let offset = offset
             .shift_y(bot_wall.square.h)
             .shift_x(self.cfg.thickness);
 
-        let front_wall = SquareElement::new(
-            back_wall.square.w - self.cfg.thick_n(2),
-            self.cfg.height,
-        )
-        .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut);
+        let mut front_wall =
+            SquareElement::new(back_wall.square.w - self.cfg.thick_n(2), self.cfg.height)
+                .borders(CutType::Nope, CutType::Cut, CutType::Cut, CutType::Cut)
+                .role(Role::Wall);
+        let notch = self
+            .cfg
+            .notch
+            .and_then(|n| n.placed(NotchPlace::Front, front_wall.square.w, front_wall.square.h));
+        if let Some(notch) = notch {
+            front_wall = front_wall.notch(Side::Bottom, notch.radius);
+        }
 
-        self.result.append(front_wall.draw(offset));
+        self.result.append(
+            front_wall
+                .named("front")
+                .hinged("bottom", Side::Top, 90.0)
+                .draw(offset),
+        );
         self.result
             .append(self.square_cut().draw(offset.origin(Origin::TopRight)));
         self.result
@@ -344,65 +759,128 @@ impl VinylBox {
         self.offset.y = offset.shift_y(front_wall.square.h).y;
     }
 
+    /// Reinforcement band inside the box: front piece and sides with the back in one piece,
+    /// handles of the sides are lined up with the wall handles
     fn draw_bottom_stripe(&mut self) {
-        let offset = self.offset.shift_y(5.0);
+        if !self.cfg.stripe {
+            return;
+        }
+
+        let offset = self.offset.shift_y(STRIPE_GAP);
 
         let front = SquareElement::cut(
-            self.cfg.length - (self.cfg.thick_n(2) + self.cfg.glue_flap * 2.0),
+            self.cfg.length - (self.cfg.thick_n(2) + self.cfg.glue_flap * Decimal::TWO),
             STRIPE_H,
-        );
+        )
+        .role(Role::Insert);
 
         self.result
             .append(front.draw(offset.shift_x(self.cfg.glue_flap)));
 
-        /*
-        let offset = offset.shift_y(STRIPE_H + 5.0);
+        let offset = offset.shift_y(STRIPE_H + STRIPE_GAP);
 
-        let side = SquareElement::cut(self.cfg.width, STRIPE_H).border_right(CutType::Bend);
-        let (side_off, handle) = self.handle_hole(true);
+        let side = SquareElement::cut(self.cfg.width, STRIPE_H)
+            .border_right(CutType::Bend)
+            .role(Role::Insert);
+        let center = SquareElement::new(self.cfg.length, STRIPE_H)
+            .border_top(CutType::Cut)
+            .border_bottom(CutType::Cut)
+            .role(Role::Insert);
 
         self.result
             .append(side.draw(offset.origin(Origin::TopRight)));
+        self.result.append(center.draw(offset));
+        self.result
+            .append(side.mirror_vertical().draw(offset.shift_x(center.square.w)));
 
-        self.result.append(
-            handle.draw(
-                offset
-                    .shift_nx(side_off)
-                    .shift_y(STRIPE_HANDLE_OFFSET)
-                    .origin(Origin::TopRight),
-            ),
-        );
+        let (side_off, handle) = self.handle_hole(true);
+        let handle_top_offset = self.handle_top_offset();
 
-        self.result
-            .append(side.mirror_vertical().draw(offset.shift_x(self.cfg.length)));
+        if handle_top_offset + self.cfg.handle.height < STRIPE_H {
+            // Same distance from the back as in the side walls
+            self.result.append(
+                handle.draw(
+                    offset
+                        .shift_nx(side_off - self.cfg.thickness)
+                        .shift_y(handle_top_offset)
+                        .origin(Origin::TopRight),
+                ),
+            );
 
-        self.result.append(
-            handle.draw(
-                offset
-                    .shift_x(self.cfg.length + side_off)
-                    .shift_y(STRIPE_HANDLE_OFFSET),
-            ),
-        );
+            self.result.append(
+                handle.draw(
+                    offset
+                        .shift_x(center.square.w + side_off - self.cfg.thickness)
+                        .shift_y(handle_top_offset),
+                ),
+            );
+        } else {
+            log::warn!(
+                "Ручка не поместилась в полосу усиления высотой {STRIPE_H}мм, в полосе ее не будет"
+            );
+        }
+    }
 
-        let center = SquareElement::new(self.cfg.length, STRIPE_H)
-            .border_top(CutType::Cut)
-            .border_bottom(CutType::Cut);
+    /// Divider cards below the box, tab of every next card is moved to the next position
+    fn draw_dividers(&mut self) {
+        if self.cfg.dividers == 0 {
+            return;
+        }
 
-        self.result.append(center.draw(offset));
-        */
+        // Inside of the box without the reinforcement band, the tab reaches the rim
+        let w = self.cfg.length - self.cfg.thick_n(6);
+        let h = self.cfg.height - self.cfg.thick_n(3);
+        let tabs = Decimal::from(self.cfg.tabs);
+        let tab_w = w / tabs;
+
+        // Cards go in rows as wide as the box drawing, a row of all tab positions on own sheet
+        let per_row = if self.cfg.dividers_only {
+            self.cfg.tabs
+        } else {
+            let sheet_w = self.result.max.x - VIEWPORT_OFFSET + STRIPE_GAP;
+            (sheet_w / (w + STRIPE_GAP))
+                .floor()
+                .to_u32()
+                .unwrap_or_default()
+                .max(1)
+        };
+        let start = if self.cfg.dividers_only {
+            Point::new(VIEWPORT_OFFSET, VIEWPORT_OFFSET)
+        } else {
+            Point::new(VIEWPORT_OFFSET, self.result.max.y + STRIPE_GAP)
+        };
+
+        for i in 0..self.cfg.dividers {
+            let x0 = tab_w * Decimal::from(i % self.cfg.tabs);
+            let vertices = divider_outline(w, h, x0, x0 + tab_w);
+
+            let card = PolygonElement::new(&vertices, &vec![CutType::Cut; vertices.len()])
+                .expect("Card outline has the tab")
+                .role(Role::Insert);
+
+            let (row, col) = (i / per_row, i % per_row);
+            self.result.append(card.draw(start.shift_xy(
+                (w + STRIPE_GAP) * Decimal::from(col),
+                (h + STRIPE_GAP) * Decimal::from(row),
+            )));
+        }
+    }
+
+    /// Handles are kept below the lid
+    fn handle_top_offset(&self) -> Decimal {
+        STRIPE_HANDLE_TOP_OFFSET.max(self.cfg.lid_height)
     }
 
-    fn draw_vertical_half_stripes(&mut self, width: f64, offset: Point) {
+    fn draw_vertical_half_stripes(&mut self, width: Decimal, offset: Point) {
         let (side_off, handle) = self.handle_hole(false);
 
         let top = SquareElement::cut(STRIPE_H, self.cfg.width + self.cfg.thickness)
-            .border_bottom(CutType::Bend);
-        let center = SquareElement::cut(STRIPE_H, self.cfg.length / 2.0).border_top(CutType::Nope);
-        let handle_top_offset = if STRIPE_HANDLE_TOP_OFFSET < self.cfg.lid_height {
-            self.cfg.lid_height
-        } else {
-            STRIPE_HANDLE_TOP_OFFSET
-        };
+            .border_bottom(CutType::Bend)
+            .role(Role::Insert);
+        let center = SquareElement::cut(STRIPE_H, self.cfg.length / Decimal::TWO)
+            .border_top(CutType::Nope)
+            .role(Role::Insert);
+        let handle_top_offset = self.handle_top_offset();
 
         self.result
             .append(top.draw(offset.origin(Origin::TopRight)));
@@ -428,7 +906,7 @@ impl VinylBox {
 
             // right hole
             self.result.append(
-                handle.draw(
+                handle.mirror_vertical().draw(
                     offset
                         .shift_y(side_off + self.cfg.thickness)
                         .shift_x(width + handle_top_offset),
@@ -437,29 +915,33 @@ impl VinylBox {
         }
     }
 
-    fn handle_hole(&self, horizontal: bool) -> (f64, SquareElement) {
-        let height = 25.0;
-        let max_w = 80.0;
-        let min_w = 10.0;
-        let min_side_offset = 25.0;
-
-        if min_side_offset * min_w < self.cfg.width {
-            return (0.0, SquareElement::new(min_w, min_w));
-        }
-
-        let mut width = self.cfg.width - min_side_offset * 2.0;
-        if width > max_w {
-            width = max_w;
-        } else if width < min_w {
-            width = min_w;
-        }
+    fn handle_hole(&self, horizontal: bool) -> (Decimal, HandleElement) {
+        self.cfg.handle.fit(self.cfg.width, horizontal)
+    }
+}
 
-        let side_offset = (self.cfg.width - width) / 2.0;
+#[cfg(test)]
+mod tests {
+    use crate::common::args::ModelParams;
+    use crate::common::Role;
+
+    fn holes(handle_height: &str) -> usize {
+        let mut p = ModelParams::new("vinyl");
+        p.set("w", "100");
+        p.set("handle-height", handle_height);
+        let drawing = crate::draw_params(&p).unwrap();
+        drawing
+            .net
+            .panels
+            .iter()
+            .filter(|p| p.role == Role::Hole)
+            .count()
+    }
 
-        if horizontal {
-            (side_offset, SquareElement::cut(width, height))
-        } else {
-            (side_offset, SquareElement::cut(height, width))
-        }
+    #[test]
+    fn band_handles_stop_at_the_band_edge() {
+        // Handle from 35mm to 89mm still fits the 90mm band, walls keep their handles anyway
+        assert_eq!(holes("54"), 6);
+        assert_eq!(holes("56"), 4);
     }
 }
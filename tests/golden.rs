@@ -0,0 +1,334 @@
+//! Golden files for every model: the binary renders SVG, it gets normalised
+//! (rounded coordinates, sorted paths) and compared with `tests/golden/<case>.svg`.
+//!
+//! When geometry changes on purpose re-bless snapshots with
+//! `BOXCUT_BLESS=1 cargo test --test golden`.
+use std::path::PathBuf;
+use std::process::Command;
+
+use svg::node::element::tag::Type;
+use svg::parser::Event;
+
+/// Case name and command line of the model
+const CASES: &[(&str, &str)] = &[
+    ("box-cuboid", "box-cuboid -l 300 -w 200 -h 100"),
+    (
+        "box-cuboid-small",
+        "box-cuboid -l 120 -w 80 -h 50 --lid 20 -t 3 --glue-flap 25",
+    ),
+    (
+        "box-cuboid-rounded",
+        "box-cuboid -l 300 -w 200 -h 100 --flap-style rounded",
+    ),
+    (
+        "box-cuboid-handles",
+        "box-cuboid -l 300 -w 200 -h 120 --handles --handle d --handle-top 40",
+    ),
+    (
+        "box-cuboid-lid-fat",
+        "box-cuboid -l 300 -w 200 -h 100 --lid-fat",
+    ),
+    (
+        "box-cuboid-notch",
+        "box-cuboid -l 300 -w 200 -h 100 --notch 15 --notch-on front",
+    ),
+    (
+        "box-cuboid-nest",
+        "box-cuboid -l 160 -w 120 -h 60 --lid 20 --nest 3 --nest-contents --clearance 1",
+    ),
+    (
+        "box-with-lid",
+        "box-with-lid -l 300 -w 200 -h 100 --lid-thickness 1.5 --clearance 1",
+    ),
+    ("lid", "lid -l 300 -w 200 -h 50"),
+    ("lid-fat", "lid -l 250 -w 150 -h 40 --fat"),
+    ("lid-glued", "lid -l 300 -w 200 -h 50 --type glued"),
+    ("lid-joined", "lid -l 300 -w 200 -h 50 --type joined"),
+    ("lid-notch", "lid -l 300 -w 200 -h 50 --notch 15"),
+    (
+        "lid-trapezoid",
+        "lid -l 250 -w 150 -h 40 --flap-style trapezoid --flap-angle 45",
+    ),
+    ("vinyl-chamfer", "vinyl -w 100 --flap-style chamfer"),
+    (
+        "vinyl-d-handle",
+        "vinyl -w 120 --handle d --handle-height 30 --handle-width 60 --handle-offset 20",
+    ),
+    ("vinyl", "vinyl -w 100"),
+    (
+        "vinyl-dividers",
+        "vinyl -w 100 --dividers 4 --tabs 3 --dividers-only",
+    ),
+    ("vinyl-lid-fat", "vinyl -w 100 --lid-fat"),
+    ("vinyl-notch", "vinyl -w 100 --notch 12"),
+    (
+        "vinyl-singles",
+        "vinyl --format 7 --records 30 --sleeve 1.5",
+    ),
+    ("vinyl-thin", "vinyl -w 60 --lid 50 -t 1.5"),
+];
+
+/// Coordinates are compared with this number of decimals
+const PRECISION: usize = 3;
+
+fn round(v: &str) -> String {
+    let Ok(n) = v.parse::<f64>() else {
+        return v.to_string();
+    };
+    let s = format!("{n:.PRECISION$}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".into()
+    } else {
+        s.into()
+    }
+}
+
+/// Round every number in the attribute value
+fn round_all(value: &str) -> String {
+    let mut res = String::new();
+    let mut num = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() || c == '.' || (c == '-' && num.is_empty()) {
+            num.push(c);
+        } else {
+            if !num.is_empty() {
+                res.push_str(&round(&num));
+                num.clear();
+            }
+            res.push(c);
+        }
+    }
+    res.push_str(&round(&num));
+    res
+}
+
+/// Sheet size and sorted cut paths, without metadata which depends on the version
+fn normalise(content: &str) -> String {
+    let mut header = String::new();
+    let mut paths = Vec::new();
+
+    for event in svg::read(content).expect("SVG is not valid") {
+        match event {
+            Event::Tag("svg", Type::Start, attrs) => {
+                header = format!(
+                    "<svg width=\"{}\" height=\"{}\" viewBox=\"{}\">",
+                    round_all(&attrs["width"]),
+                    round_all(&attrs["height"]),
+                    round_all(&attrs["viewBox"]),
+                );
+            }
+            Event::Tag("path", _, attrs) => {
+                paths.push(format!(
+                    "<path d=\"{}\" stroke=\"{}\"/>",
+                    round_all(&attrs["d"]),
+                    &*attrs["stroke"],
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    paths.sort();
+    format!("{header}\n{}\n</svg>\n", paths.join("\n"))
+}
+
+fn render(name: &str, args: &str) -> String {
+    let file =
+        std::env::temp_dir().join(format!("boxcut-golden-{}-{name}.svg", std::process::id()));
+
+    let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+        .arg("-f")
+        .arg(&file)
+        .args(args.split_whitespace())
+        .output()
+        .expect("boxcut did not start");
+    assert!(
+        out.status.success(),
+        "boxcut {args} failed:\n{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let content = std::fs::read_to_string(&file).unwrap();
+    let _ = std::fs::remove_file(&file);
+    normalise(&content)
+}
+
+#[test]
+fn models_match_golden_files() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let bless = std::env::var_os("BOXCUT_BLESS").is_some();
+    let mut failed = Vec::new();
+
+    for (name, args) in CASES {
+        let actual = render(name, args);
+        let path = dir.join(format!("{name}.svg"));
+
+        if bless {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            continue;
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => {
+                let diff: Vec<_> = expected
+                    .lines()
+                    .filter(|l| !actual.lines().any(|a| a == *l))
+                    .map(|l| format!("- {l}"))
+                    .chain(
+                        actual
+                            .lines()
+                            .filter(|l| !expected.lines().any(|e| e == *l))
+                            .map(|l| format!("+ {l}")),
+                    )
+                    .collect();
+                failed.push(format!("{name}:\n{}", diff.join("\n")));
+            }
+            Err(_) => failed.push(format!("{name}: no golden file {}", path.display())),
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "Output differs from golden files, run with BOXCUT_BLESS=1 if it is intended\n{}",
+        failed.join("\n")
+    );
+}
+
+#[test]
+fn stdout_gets_the_same_drawing() {
+    let (name, args) = CASES[0];
+    let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+        .args(["-f", "-"])
+        .args(args.split_whitespace())
+        .output()
+        .expect("boxcut did not start");
+    assert!(out.status.success());
+
+    assert_eq!(
+        normalise(&String::from_utf8(out.stdout).unwrap()),
+        render(name, args)
+    );
+}
+
+#[test]
+fn manifest_lists_panels_next_to_the_drawing() {
+    let file = std::env::temp_dir().join(format!("boxcut-manifest-{}.svg", std::process::id()));
+    let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+        .arg("--manifest")
+        .arg("-f")
+        .arg(&file)
+        .args("box-cuboid -l 300 -w 200 -h 100".split_whitespace())
+        .output()
+        .expect("boxcut did not start");
+    assert!(out.status.success());
+
+    let json = file.with_extension("json");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    let _ = std::fs::remove_file(&file);
+    let _ = std::fs::remove_file(&json);
+
+    assert_eq!(manifest["model"], "box-cuboid");
+    assert_eq!(manifest["params"]["length"], "300");
+    let bottom = manifest["panels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == "bottom")
+        .unwrap();
+    assert_eq!(bottom["role"], "bottom");
+    assert_eq!(
+        (bottom["width"].as_f64(), bottom["height"].as_f64()),
+        (Some(300.0), Some(200.0))
+    );
+    assert_eq!(bottom["area"].as_f64(), Some(60000.0));
+    assert_eq!(manifest["glue"].as_array().unwrap().len(), 6);
+}
+
+#[test]
+fn zero_glue_flap_is_an_error_not_a_panic() {
+    let models = [
+        "box-cuboid -l 300 -w 200 -h 100 --lid 30",
+        "box-with-lid -l 300 -w 200 -h 100",
+        "lid -l 300 -w 200 -h 50",
+        "vinyl -w 100",
+    ];
+    for args in models {
+        for style in ["square", "chamfer", "trapezoid", "rounded"] {
+            let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+                .args(["-f", "-"])
+                .args(args.split_whitespace())
+                .args(["--glue-flap", "0", "--flap-style", style])
+                .output()
+                .expect("boxcut did not start");
+            assert_eq!(
+                out.status.code(),
+                Some(42),
+                "boxcut {args} --flap-style {style}:\n{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        }
+    }
+}
+
+#[test]
+fn sweep_writes_nothing_when_names_repeat() {
+    let dir = std::env::temp_dir().join(format!("boxcut-sweep-{}", std::process::id()));
+    let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+        .arg("sweep")
+        .arg("--dir")
+        .arg(&dir)
+        .args("--name {model}_{l} box-cuboid -l 200 -w 100,150 -h 80".split_whitespace())
+        .output()
+        .expect("boxcut did not start");
+    assert_eq!(out.status.code(), Some(42));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn manifest_without_a_drawing_file_writes_nothing() {
+    let file = std::env::temp_dir().join(format!("boxcut-manifest-{}.json", std::process::id()));
+    for target in [file.to_str().unwrap(), "-"] {
+        let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+            .args(["--manifest", "-f", target])
+            .args("lid -l 300 -w 200 -h 50".split_whitespace())
+            .output()
+            .expect("boxcut did not start");
+        assert_eq!(out.status.code(), Some(42), "{target}");
+        assert!(out.stdout.is_empty(), "{target}");
+    }
+    assert!(!file.exists());
+}
+
+#[test]
+fn every_case_separates_from_sheet() {
+    let dir = std::env::temp_dir().join(format!("boxcut-check-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut files = Vec::new();
+    for (name, args) in CASES {
+        let file = dir.join(format!("{name}.svg"));
+        let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+            .arg("-f")
+            .arg(&file)
+            .args(args.split_whitespace())
+            .output()
+            .expect("boxcut did not start");
+        assert!(out.status.success(), "boxcut {args} failed");
+        files.push(file);
+    }
+
+    let out = Command::new(env!("CARGO_BIN_EXE_boxcut"))
+        .arg("check")
+        .args(&files)
+        .output()
+        .expect("boxcut did not start");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}